@@ -5,8 +5,7 @@ extern crate alloc;
 use alloc::vec;
 use core::hint::black_box;
 
-#[jolt::provable]
-fn matrix_mul(size: usize) -> u32 {
+fn multiply(size: usize) -> u32 {
 
     let a = vec![vec![2u32; size]; size];
     let b = vec![vec![3u32; size]; size];
@@ -28,3 +27,19 @@ fn matrix_mul(size: usize) -> u32 {
 
     sum
 }
+
+#[jolt::provable]
+fn matrix_mul(size: usize) -> u32 {
+    multiply(size)
+}
+
+// sizes beyond 32, keep the layout in sync with the driver
+#[jolt::provable(
+    max_input_size = 1048576,
+    stack_size = 65536,
+    memory_size = 67108864,
+    max_trace_length = 67108864
+)]
+fn matrix_mul_large(size: usize) -> u32 {
+    multiply(size)
+}
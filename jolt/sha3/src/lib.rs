@@ -5,11 +5,25 @@ use sha3::{Keccak256, Digest};
 use core::hint::black_box;
 
 
-#[jolt::provable]
-fn sha3(input: &[u8]) -> [u8; 32] {
+fn hash(input: &[u8]) -> [u8; 32] {
     let mut hasher = Keccak256::new();
     hasher.update(&black_box(input));
     let result = hasher.finalize();
     Into::<[u8; 32]>::into(result)
 }
 
+#[jolt::provable]
+fn sha3(input: &[u8]) -> [u8; 32] {
+    hash(input)
+}
+
+// inputs beyond the default 4 KiB, keep the layout in sync with the driver
+#[jolt::provable(
+    max_input_size = 1048576,
+    stack_size = 65536,
+    memory_size = 67108864,
+    max_trace_length = 67108864
+)]
+fn sha3_large(input: &[u8]) -> [u8; 32] {
+    hash(input)
+}
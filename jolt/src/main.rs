@@ -1,10 +1,32 @@
-use std::{time::Instant, usize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{fs, time::Instant, usize};
 
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Validate};
+use jolt::host::analyze::ProgramSummary;
+use jolt::host::Program;
 use jolt::{JoltHyperKZGProof, Serializable};
-use utils::{BenchmarkConfig, BenchmarkResult};
+use utils::memory::peak_rss;
+use utils::{hex, BenchmarkConfig, BenchmarkResult, ExecutionReport, Mode, PreprocessingReport};
 
-use clap::Parser;
+use clap::{Parser, ValueEnum};
+
+/// Polynomial commitment schemes the Jolt SDK exposes to `#[jolt::provable]` guests.
+/// The pinned SDK generates provers for HyperKZG only, schemes it adds go here.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Pcs {
+    #[value(name = "hyperkzg")]
+    HyperKzg,
+}
+
+impl std::fmt::Display for Pcs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_possible_value().unwrap().get_name())
+    }
+}
 
 #[derive(Parser, Debug)]
 #[clap()]
@@ -17,8 +39,20 @@ pub struct Cli {
 
     #[arg(long, default_value = "1")]
     pub verifier_iterations: u32,
+
+    #[arg(long, value_enum, default_value_t = Mode::Prove)]
+    pub mode: Mode,
+
+    /// Polynomial commitment scheme of the proof
+    #[arg(long, value_enum, default_value_t = Pcs::HyperKzg)]
+    pub pcs: Pcs,
 }
 
+/// Directory to keep the preprocessing of every guest in, it is reused by later runs
+/// of the same guest (e.g. for other n). Not a backend option, so that it stays out
+/// of the benchmark id and the result of a run does not depend on where it is cached.
+const PREPROCESSING_CACHE_VAR: &str = "JOLT_PREPROCESSING_CACHE";
+
 fn main() {
     let cli = Cli::parse();
 
@@ -26,9 +60,11 @@ fn main() {
         n: cli.n,
         program: cli.program.clone(),
         verifier_iterations: cli.verifier_iterations,
+        mode: cli.mode,
+        ..Default::default()
     };
 
-    let result = match cli.program.as_str() {
+    let mut result = match cli.program.as_str() {
         "fib" => benchmark_fib(&config),
         "sha2" => benchmark_sha2(&config),
         "sha2-chain" => benchmark_sha2_chain(&config),
@@ -40,16 +76,230 @@ fn main() {
         "blake-chain" => benchmark_blake_chain(&config),
         _ => unreachable!(),
     };
+    // next to the layout settings, so every proof says which scheme it used
+    if config.mode == Mode::Prove {
+        result
+            .settings
+            .insert("pcs".to_string(), cli.pcs.to_string());
+    }
 
     std::fs::write("results.json", result.to_json()).unwrap();
 }
 
+/// Trace the guest without proving, the guest has to be compiled beforehand
+fn execute(trace: impl FnOnce() -> ProgramSummary) -> BenchmarkResult {
+    let start = Instant::now();
+    let summary = trace();
+    let duration = start.elapsed();
+
+    let trace_length = summary.trace_len();
+    BenchmarkResult {
+        cycle_count: trace_length,
+        execution: Some(ExecutionReport {
+            duration,
+            // the prover pads the trace to a power of two
+            trace_length: Some(trace_length.next_power_of_two() as u64),
+            public_output: hex(&summary.io_device.outputs),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// Memory layout a guest function is compiled with, as in its `#[jolt::provable]` attributes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Layout {
+    name: &'static str,
+    max_input_size: usize,
+    max_output_size: usize,
+    stack_size: usize,
+    memory_size: usize,
+    max_trace_length: usize,
+}
+
+/// The defaults of a bare `#[jolt::provable]`
+const DEFAULT_LAYOUT: Layout = Layout {
+    name: "default",
+    max_input_size: 4096,
+    max_output_size: 4096,
+    stack_size: 4096,
+    memory_size: 10 << 20,
+    max_trace_length: 1 << 24,
+};
+
+/// Layout of the `*_large` guest functions, keep in sync with their attributes
+const LARGE_LAYOUT: Layout = Layout {
+    name: "large",
+    max_input_size: 1 << 20,
+    max_output_size: 4096,
+    stack_size: 1 << 16,
+    memory_size: 1 << 26,
+    max_trace_length: 1 << 26,
+};
+
+// exit code of a run that does not fit the guest layout,
+// to tell it apart from a panic of the prover (101)
+const LAYOUT_EXCEEDED_EXIT_CODE: i32 = 3;
+
+/// Give up on a run that does not fit its guest layout, before proving it
+fn layout_exceeded(reason: String) -> ! {
+    eprintln!("{}", reason);
+    std::process::exit(LAYOUT_EXCEEDED_EXIT_CODE);
+}
+
+impl Layout {
+    /// The smallest layout a byte input of `len` bytes fits into
+    fn for_input(len: usize) -> Layout {
+        // the input is serialized with its length as a varint
+        let serialized = len + 5;
+        if serialized <= DEFAULT_LAYOUT.max_input_size {
+            DEFAULT_LAYOUT
+        } else if serialized <= LARGE_LAYOUT.max_input_size {
+            LARGE_LAYOUT
+        } else {
+            layout_exceeded(format!(
+                "input of {} bytes exceeds the max_input_size of every guest layout",
+                len
+            ))
+        }
+    }
+
+    /// The smallest layout multiplying matrices of `size` fits into
+    fn for_mat_mul(size: usize) -> Layout {
+        if size <= 32 {
+            DEFAULT_LAYOUT
+        } else {
+            LARGE_LAYOUT
+        }
+    }
+
+    /// Trace the guest and give up if its padded trace is longer than
+    /// `max_trace_length`, which the prover would only notice by panicking
+    fn check_trace_length(&self, trace: impl FnOnce() -> ProgramSummary) {
+        let trace_length = trace().trace_len().next_power_of_two();
+        if trace_length > self.max_trace_length {
+            layout_exceeded(format!(
+                "trace of {} rows exceeds the max_trace_length of the {} layout ({})",
+                trace_length, self.name, self.max_trace_length
+            ));
+        }
+    }
+
+    /// The layout as driver settings of the result
+    fn settings(&self) -> BTreeMap<String, String> {
+        [
+            ("layout", self.name.to_string()),
+            ("max_input_size", self.max_input_size.to_string()),
+            ("max_output_size", self.max_output_size.to_string()),
+            ("stack_size", self.stack_size.to_string()),
+            ("memory_size", self.memory_size.to_string()),
+            ("max_trace_length", self.max_trace_length.to_string()),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect()
+    }
+}
+
+/// Build the guest, returning how long it took
+fn compile(compile: impl FnOnce() -> Program) -> (Program, Duration) {
+    let start = Instant::now();
+    let program = compile();
+    (program, start.elapsed())
+}
+
+/// Preprocess the guest for the prover and the verifier. With a preprocessing cache
+/// (`JOLT_PREPROCESSING_CACHE`), the preprocessing of an earlier run of the same guest
+/// is loaded instead.
+fn preprocess<P, V>(
+    config: &BenchmarkConfig,
+    program: &Program,
+    compile_duration: Duration,
+    preprocess_prover: impl FnOnce() -> P,
+    preprocess_verifier: impl FnOnce() -> V,
+) -> (P, V, PreprocessingReport)
+where
+    P: CanonicalSerialize + CanonicalDeserialize,
+    V: CanonicalSerialize + CanonicalDeserialize,
+{
+    // the preprocessing only depends on the guest, so the cache is keyed by its ELF
+    let cache = std::env::var_os(PREPROCESSING_CACHE_VAR).map(|dir| {
+        let mut hasher = DefaultHasher::new();
+        program
+            .get_elf_contents()
+            .expect("guest is compiled")
+            .hash(&mut hasher);
+        fs::create_dir_all(&dir).unwrap();
+        Path::new(&dir).join(format!("{}-{:016x}", config.program, hasher.finish()))
+    });
+
+    let (prover, prover_duration, prover_size, prover_reused) = load_or_compute(
+        cache.as_ref().map(|path| path.with_extension("prover")),
+        preprocess_prover,
+    );
+    let (verifier, verifier_duration, verifier_size, verifier_reused) = load_or_compute(
+        cache.as_ref().map(|path| path.with_extension("verifier")),
+        preprocess_verifier,
+    );
+
+    let report = PreprocessingReport {
+        compile_duration,
+        prover_duration,
+        verifier_duration,
+        prover_size,
+        verifier_size,
+        peak_memory: peak_rss(),
+        reused: prover_reused && verifier_reused,
+    };
+    (prover, verifier, report)
+}
+
+/// Load a preprocessing from `path` if it exists, otherwise compute it and save it there.
+/// Returns it with the time it took, its serialized size and whether it was loaded.
+fn load_or_compute<T>(
+    path: Option<PathBuf>,
+    compute: impl FnOnce() -> T,
+) -> (T, Duration, usize, bool)
+where
+    T: CanonicalSerialize + CanonicalDeserialize,
+{
+    let start = Instant::now();
+    if let Some(bytes) = path.as_ref().and_then(|path| fs::read(path).ok()) {
+        let value = T::deserialize_with_mode(&bytes[..], Compress::Yes, Validate::Yes).unwrap();
+        return (value, start.elapsed(), bytes.len(), true);
+    }
+    let value = compute();
+    let duration = start.elapsed();
+
+    let mut bytes = Vec::new();
+    value
+        .serialize_with_mode(&mut bytes, Compress::Yes)
+        .unwrap();
+    if let Some(path) = path {
+        fs::write(path, &bytes).unwrap();
+    }
+    (value, duration, bytes.len(), false)
+}
+
 fn benchmark_sha2_chain(config: &BenchmarkConfig) -> BenchmarkResult {
     let target_dir = "/tmp/jolt-guest-targets";
-    let program = sha2_chain_guest::compile_sha2_chain(target_dir);
+    let (program, compile_duration) = compile(|| sha2_chain_guest::compile_sha2_chain(target_dir));
+    let layout = DEFAULT_LAYOUT;
+    let analyze = || sha2_chain_guest::analyze_sha2_chain([5u8; 32], config.n);
+    if config.mode == Mode::Execute {
+        let mut result = execute(analyze);
+        result.settings = layout.settings();
+        return result;
+    }
+    layout.check_trace_length(analyze);
 
-    let prover_preprocessing = sha2_chain_guest::preprocess_prover_sha2_chain(&program);
-    let verifier_preprocessing = sha2_chain_guest::preprocess_verifier_sha2_chain(&program);
+    let (prover_preprocessing, verifier_preprocessing, preprocessing) = preprocess(
+        config,
+        &program,
+        compile_duration,
+        || sha2_chain_guest::preprocess_prover_sha2_chain(&program),
+        || sha2_chain_guest::preprocess_verifier_sha2_chain(&program),
+    );
 
     let prove_sha2_chain = sha2_chain_guest::build_prover_sha2_chain(program, prover_preprocessing);
     let verify_sha2_chain = sha2_chain_guest::build_verifier_sha2_chain(verifier_preprocessing);
@@ -66,20 +316,32 @@ fn benchmark_sha2_chain(config: &BenchmarkConfig) -> BenchmarkResult {
 
     let mut verifier_durations = Vec::new();
 
-    // For the first verification, we already have the proof
+    // Serialize proof once for reuse across iterations
+    let mut proof_bytes = Vec::new();
+    proof
+        .serialize_with_mode(&mut proof_bytes, Compress::Yes)
+        .unwrap();
+
+    // For the first verification, deserialize and use the proof
     let verify_start = Instant::now();
-    let is_valid = verify_sha2_chain(input, config.n, output, proof);
+    let proof_clone =
+        JoltHyperKZGProof::deserialize_with_mode(&proof_bytes[..], Compress::Yes, Validate::Yes)
+            .unwrap();
+    let is_valid = verify_sha2_chain(input, config.n, output, proof_clone);
     assert!(is_valid);
     let verify_end = Instant::now();
     verifier_durations.push(verify_end.duration_since(verify_start));
 
-    // For additional iterations, we need to generate new proofs since Jolt consumes them
+    // For additional iterations, deserialize a fresh proof each time
     for _ in 1..config.verifier_iterations {
-        let (verify_output, verify_proof) = prove_sha2_chain(input, config.n);
-        assert_eq!(output, verify_output);
-
         let verify_start = Instant::now();
-        let is_valid = verify_sha2_chain(input, config.n, verify_output, verify_proof);
+        let proof_clone = JoltHyperKZGProof::deserialize_with_mode(
+            &proof_bytes[..],
+            Compress::Yes,
+            Validate::Yes,
+        )
+        .unwrap();
+        let is_valid = verify_sha2_chain(input, config.n, output, proof_clone);
         assert!(is_valid);
         let verify_end = Instant::now();
         verifier_durations.push(verify_end.duration_since(verify_start));
@@ -90,16 +352,31 @@ fn benchmark_sha2_chain(config: &BenchmarkConfig) -> BenchmarkResult {
         prover_durations: vec![prover_duration],
         verifier_durations,
         cycle_count: trace_len,
+        preprocessing: Some(preprocessing),
+        settings: layout.settings(),
         ..Default::default()
     }
 }
 
 fn benchmark_sha3_chain(config: &BenchmarkConfig) -> BenchmarkResult {
     let target_dir = "/tmp/jolt-guest-targets";
-    let program = sha3_chain_guest::compile_sha3_chain(target_dir);
+    let (program, compile_duration) = compile(|| sha3_chain_guest::compile_sha3_chain(target_dir));
+    let layout = DEFAULT_LAYOUT;
+    let analyze = || sha3_chain_guest::analyze_sha3_chain([5u8; 32], config.n);
+    if config.mode == Mode::Execute {
+        let mut result = execute(analyze);
+        result.settings = layout.settings();
+        return result;
+    }
+    layout.check_trace_length(analyze);
 
-    let prover_preprocessing = sha3_chain_guest::preprocess_prover_sha3_chain(&program);
-    let verifier_preprocessing = sha3_chain_guest::preprocess_verifier_sha3_chain(&program);
+    let (prover_preprocessing, verifier_preprocessing, preprocessing) = preprocess(
+        config,
+        &program,
+        compile_duration,
+        || sha3_chain_guest::preprocess_prover_sha3_chain(&program),
+        || sha3_chain_guest::preprocess_verifier_sha3_chain(&program),
+    );
 
     let prove_sha3_chain = sha3_chain_guest::build_prover_sha3_chain(program, prover_preprocessing);
     let verify_sha3_chain = sha3_chain_guest::build_verifier_sha3_chain(verifier_preprocessing);
@@ -115,20 +392,32 @@ fn benchmark_sha3_chain(config: &BenchmarkConfig) -> BenchmarkResult {
 
     let mut verifier_durations = Vec::new();
 
-    // For the first verification, we already have the proof
+    // Serialize proof once for reuse across iterations
+    let mut proof_bytes = Vec::new();
+    proof
+        .serialize_with_mode(&mut proof_bytes, Compress::Yes)
+        .unwrap();
+
+    // For the first verification, deserialize and use the proof
     let verify_start = Instant::now();
-    let is_valid = verify_sha3_chain(input, config.n, output, proof);
+    let proof_clone =
+        JoltHyperKZGProof::deserialize_with_mode(&proof_bytes[..], Compress::Yes, Validate::Yes)
+            .unwrap();
+    let is_valid = verify_sha3_chain(input, config.n, output, proof_clone);
     assert!(is_valid);
     let verify_end = Instant::now();
     verifier_durations.push(verify_end.duration_since(verify_start));
 
-    // For additional iterations, we need to generate new proofs since Jolt consumes them
+    // For additional iterations, deserialize a fresh proof each time
     for _ in 1..config.verifier_iterations {
-        let (verify_output, verify_proof) = prove_sha3_chain(input, config.n);
-        assert_eq!(output, verify_output);
-
         let verify_start = Instant::now();
-        let is_valid = verify_sha3_chain(input, config.n, verify_output, verify_proof);
+        let proof_clone = JoltHyperKZGProof::deserialize_with_mode(
+            &proof_bytes[..],
+            Compress::Yes,
+            Validate::Yes,
+        )
+        .unwrap();
+        let is_valid = verify_sha3_chain(input, config.n, output, proof_clone);
         assert!(is_valid);
         let verify_end = Instant::now();
         verifier_durations.push(verify_end.duration_since(verify_start));
@@ -139,19 +428,75 @@ fn benchmark_sha3_chain(config: &BenchmarkConfig) -> BenchmarkResult {
         prover_durations: vec![end.duration_since(start)],
         verifier_durations,
         cycle_count: trace_len,
+        preprocessing: Some(preprocessing),
+        settings: layout.settings(),
         ..Default::default()
     }
 }
 
 fn benchmark_sha3(config: &BenchmarkConfig) -> BenchmarkResult {
     let target_dir = "/tmp/jolt-guest-targets";
-    let program = sha3_guest::compile_sha3(target_dir);
-
-    let prover_preprocessing = sha3_guest::preprocess_prover_sha3(&program);
-    let verifier_preprocessing = sha3_guest::preprocess_verifier_sha3(&program);
-
-    let prove_sha3 = sha3_guest::build_prover_sha3(program, prover_preprocessing);
-    let verify_sha3 = sha3_guest::build_verifier_sha3(verifier_preprocessing);
+    let layout = Layout::for_input(config.n as usize);
+    let large = layout == LARGE_LAYOUT;
+    eprintln!("Guest layout: {:?}", layout);
+
+    let (program, compile_duration) = compile(|| {
+        if large {
+            sha3_guest::compile_sha3_large(target_dir)
+        } else {
+            sha3_guest::compile_sha3(target_dir)
+        }
+    });
+    let analyze = || {
+        if large {
+            sha3_guest::analyze_sha3_large(&vec![5u8; config.n as usize])
+        } else {
+            sha3_guest::analyze_sha3(&vec![5u8; config.n as usize])
+        }
+    };
+    if config.mode == Mode::Execute {
+        let mut result = execute(analyze);
+        result.settings = layout.settings();
+        return result;
+    }
+    layout.check_trace_length(analyze);
+
+    let (prover_preprocessing, verifier_preprocessing, preprocessing) = preprocess(
+        config,
+        &program,
+        compile_duration,
+        || {
+            if large {
+                sha3_guest::preprocess_prover_sha3_large(&program)
+            } else {
+                sha3_guest::preprocess_prover_sha3(&program)
+            }
+        },
+        || {
+            if large {
+                sha3_guest::preprocess_verifier_sha3_large(&program)
+            } else {
+                sha3_guest::preprocess_verifier_sha3(&program)
+            }
+        },
+    );
+
+    // the functions of both layouts have the same signature
+    let prove_sha3: Box<dyn Fn(&[u8]) -> ([u8; 32], JoltHyperKZGProof)> = if large {
+        Box::new(sha3_guest::build_prover_sha3_large(
+            program,
+            prover_preprocessing,
+        ))
+    } else {
+        Box::new(sha3_guest::build_prover_sha3(program, prover_preprocessing))
+    };
+    let verify_sha3: Box<dyn Fn(&[u8], [u8; 32], JoltHyperKZGProof) -> bool> = if large {
+        Box::new(sha3_guest::build_verifier_sha3_large(
+            verifier_preprocessing,
+        ))
+    } else {
+        Box::new(sha3_guest::build_verifier_sha3(verifier_preprocessing))
+    };
 
     let input = vec![5u8; config.n as usize];
     let input = input.as_slice();
@@ -201,19 +546,75 @@ fn benchmark_sha3(config: &BenchmarkConfig) -> BenchmarkResult {
         prover_durations: vec![end.duration_since(start)],
         verifier_durations,
         cycle_count: trace_len,
+        preprocessing: Some(preprocessing),
+        settings: layout.settings(),
         ..Default::default()
     }
 }
 
 fn benchmark_sha2(config: &BenchmarkConfig) -> BenchmarkResult {
     let target_dir = "/tmp/jolt-guest-targets";
-    let program = sha2_guest::compile_sha2(target_dir);
-
-    let prover_preprocessing = sha2_guest::preprocess_prover_sha2(&program);
-    let verifier_preprocessing = sha2_guest::preprocess_verifier_sha2(&program);
-
-    let prove_sha2 = sha2_guest::build_prover_sha2(program, prover_preprocessing);
-    let verify_sha2 = sha2_guest::build_verifier_sha2(verifier_preprocessing);
+    let layout = Layout::for_input(config.n as usize);
+    let large = layout == LARGE_LAYOUT;
+    eprintln!("Guest layout: {:?}", layout);
+
+    let (program, compile_duration) = compile(|| {
+        if large {
+            sha2_guest::compile_sha2_large(target_dir)
+        } else {
+            sha2_guest::compile_sha2(target_dir)
+        }
+    });
+    let analyze = || {
+        if large {
+            sha2_guest::analyze_sha2_large(&vec![5u8; config.n as usize])
+        } else {
+            sha2_guest::analyze_sha2(&vec![5u8; config.n as usize])
+        }
+    };
+    if config.mode == Mode::Execute {
+        let mut result = execute(analyze);
+        result.settings = layout.settings();
+        return result;
+    }
+    layout.check_trace_length(analyze);
+
+    let (prover_preprocessing, verifier_preprocessing, preprocessing) = preprocess(
+        config,
+        &program,
+        compile_duration,
+        || {
+            if large {
+                sha2_guest::preprocess_prover_sha2_large(&program)
+            } else {
+                sha2_guest::preprocess_prover_sha2(&program)
+            }
+        },
+        || {
+            if large {
+                sha2_guest::preprocess_verifier_sha2_large(&program)
+            } else {
+                sha2_guest::preprocess_verifier_sha2(&program)
+            }
+        },
+    );
+
+    // the functions of both layouts have the same signature
+    let prove_sha2: Box<dyn Fn(&[u8]) -> ([u8; 32], JoltHyperKZGProof)> = if large {
+        Box::new(sha2_guest::build_prover_sha2_large(
+            program,
+            prover_preprocessing,
+        ))
+    } else {
+        Box::new(sha2_guest::build_prover_sha2(program, prover_preprocessing))
+    };
+    let verify_sha2: Box<dyn Fn(&[u8], [u8; 32], JoltHyperKZGProof) -> bool> = if large {
+        Box::new(sha2_guest::build_verifier_sha2_large(
+            verifier_preprocessing,
+        ))
+    } else {
+        Box::new(sha2_guest::build_verifier_sha2(verifier_preprocessing))
+    };
 
     let input = vec![5u8; config.n as usize];
     let input = input.as_slice();
@@ -263,16 +664,31 @@ fn benchmark_sha2(config: &BenchmarkConfig) -> BenchmarkResult {
         prover_durations: vec![end.duration_since(start)],
         verifier_durations,
         cycle_count: trace_len,
+        preprocessing: Some(preprocessing),
+        settings: layout.settings(),
         ..Default::default()
     }
 }
 
 fn benchmark_fib(config: &BenchmarkConfig) -> BenchmarkResult {
     let target_dir = "/tmp/jolt-guest-targets";
-    let program = fibonacci_guest::compile_fib(target_dir);
+    let (program, compile_duration) = compile(|| fibonacci_guest::compile_fib(target_dir));
+    let layout = DEFAULT_LAYOUT;
+    let analyze = || fibonacci_guest::analyze_fib(config.n);
+    if config.mode == Mode::Execute {
+        let mut result = execute(analyze);
+        result.settings = layout.settings();
+        return result;
+    }
+    layout.check_trace_length(analyze);
 
-    let prover_preprocessing = fibonacci_guest::preprocess_prover_fib(&program);
-    let verifier_preprocessing = fibonacci_guest::preprocess_verifier_fib(&program);
+    let (prover_preprocessing, verifier_preprocessing, preprocessing) = preprocess(
+        config,
+        &program,
+        compile_duration,
+        || fibonacci_guest::preprocess_prover_fib(&program),
+        || fibonacci_guest::preprocess_verifier_fib(&program),
+    );
 
     let prove_fib = fibonacci_guest::build_prover_fib(program, prover_preprocessing);
     let verify_fib = fibonacci_guest::build_verifier_fib(verifier_preprocessing);
@@ -322,16 +738,31 @@ fn benchmark_fib(config: &BenchmarkConfig) -> BenchmarkResult {
         prover_durations: vec![end.duration_since(start)],
         verifier_durations,
         cycle_count: trace_len,
+        preprocessing: Some(preprocessing),
+        settings: layout.settings(),
         ..Default::default()
     }
 }
 
 fn benchmark_ecadd(config: &BenchmarkConfig) -> BenchmarkResult {
     let target_dir = "/tmp/jolt-guest-targets";
-    let program = ec_guest::compile_ecadd(target_dir);
+    let (program, compile_duration) = compile(|| ec_guest::compile_ecadd(target_dir));
+    let layout = DEFAULT_LAYOUT;
+    let analyze = || ec_guest::analyze_ecadd(config.n);
+    if config.mode == Mode::Execute {
+        let mut result = execute(analyze);
+        result.settings = layout.settings();
+        return result;
+    }
+    layout.check_trace_length(analyze);
 
-    let prover_preprocessing = ec_guest::preprocess_prover_ecadd(&program);
-    let verifier_preprocessing = ec_guest::preprocess_verifier_ecadd(&program);
+    let (prover_preprocessing, verifier_preprocessing, preprocessing) = preprocess(
+        config,
+        &program,
+        compile_duration,
+        || ec_guest::preprocess_prover_ecadd(&program),
+        || ec_guest::preprocess_verifier_ecadd(&program),
+    );
 
     let prove_ec = ec_guest::build_prover_ecadd(program, prover_preprocessing);
     let verify_ec = ec_guest::build_verifier_ecadd(verifier_preprocessing);
@@ -381,19 +812,80 @@ fn benchmark_ecadd(config: &BenchmarkConfig) -> BenchmarkResult {
         prover_durations: vec![end.duration_since(start)],
         verifier_durations,
         cycle_count: trace_len,
+        preprocessing: Some(preprocessing),
+        settings: layout.settings(),
         ..Default::default()
     }
 }
 
 fn benchmark_mat_mul(config: &BenchmarkConfig) -> BenchmarkResult {
     let target_dir = "/tmp/jolt-guest-targets";
-    let program = mat_mul_guest::compile_matrix_mul(target_dir);
-
-    let prover_preprocessing = mat_mul_guest::preprocess_prover_matrix_mul(&program);
-    let verifier_preprocessing = mat_mul_guest::preprocess_verifier_matrix_mul(&program);
-
-    let prove_mat_mul = mat_mul_guest::build_prover_matrix_mul(program, prover_preprocessing);
-    let verify_mat_mul = mat_mul_guest::build_verifier_matrix_mul(verifier_preprocessing);
+    let layout = Layout::for_mat_mul(config.n as usize);
+    let large = layout == LARGE_LAYOUT;
+    eprintln!("Guest layout: {:?}", layout);
+
+    let (program, compile_duration) = compile(|| {
+        if large {
+            mat_mul_guest::compile_matrix_mul_large(target_dir)
+        } else {
+            mat_mul_guest::compile_matrix_mul(target_dir)
+        }
+    });
+    let analyze = || {
+        if large {
+            mat_mul_guest::analyze_matrix_mul_large(config.n as usize)
+        } else {
+            mat_mul_guest::analyze_matrix_mul(config.n as usize)
+        }
+    };
+    if config.mode == Mode::Execute {
+        let mut result = execute(analyze);
+        result.settings = layout.settings();
+        return result;
+    }
+    layout.check_trace_length(analyze);
+
+    let (prover_preprocessing, verifier_preprocessing, preprocessing) = preprocess(
+        config,
+        &program,
+        compile_duration,
+        || {
+            if large {
+                mat_mul_guest::preprocess_prover_matrix_mul_large(&program)
+            } else {
+                mat_mul_guest::preprocess_prover_matrix_mul(&program)
+            }
+        },
+        || {
+            if large {
+                mat_mul_guest::preprocess_verifier_matrix_mul_large(&program)
+            } else {
+                mat_mul_guest::preprocess_verifier_matrix_mul(&program)
+            }
+        },
+    );
+
+    // the functions of both layouts have the same signature
+    let prove_mat_mul: Box<dyn Fn(usize) -> (u32, JoltHyperKZGProof)> = if large {
+        Box::new(mat_mul_guest::build_prover_matrix_mul_large(
+            program,
+            prover_preprocessing,
+        ))
+    } else {
+        Box::new(mat_mul_guest::build_prover_matrix_mul(
+            program,
+            prover_preprocessing,
+        ))
+    };
+    let verify_mat_mul: Box<dyn Fn(usize, u32, JoltHyperKZGProof) -> bool> = if large {
+        Box::new(mat_mul_guest::build_verifier_matrix_mul_large(
+            verifier_preprocessing,
+        ))
+    } else {
+        Box::new(mat_mul_guest::build_verifier_matrix_mul(
+            verifier_preprocessing,
+        ))
+    };
 
     let start = Instant::now();
     let (output, proof) = prove_mat_mul(config.n as usize);
@@ -440,16 +932,32 @@ fn benchmark_mat_mul(config: &BenchmarkConfig) -> BenchmarkResult {
         prover_durations: vec![end.duration_since(start)],
         verifier_durations,
         cycle_count: trace_len,
+        preprocessing: Some(preprocessing),
+        settings: layout.settings(),
         ..Default::default()
     }
 }
 
 fn benchmark_blake_chain(config: &BenchmarkConfig) -> BenchmarkResult {
     let target_dir = "/tmp/jolt-guest-targets";
-    let program = blake_chain_guest::compile_blake_chain(target_dir);
+    let (program, compile_duration) =
+        compile(|| blake_chain_guest::compile_blake_chain(target_dir));
+    let layout = DEFAULT_LAYOUT;
+    let analyze = || blake_chain_guest::analyze_blake_chain([5u8; 32], config.n);
+    if config.mode == Mode::Execute {
+        let mut result = execute(analyze);
+        result.settings = layout.settings();
+        return result;
+    }
+    layout.check_trace_length(analyze);
 
-    let prover_preprocessing = blake_chain_guest::preprocess_prover_blake_chain(&program);
-    let verifier_preprocessing = blake_chain_guest::preprocess_verifier_blake_chain(&program);
+    let (prover_preprocessing, verifier_preprocessing, preprocessing) = preprocess(
+        config,
+        &program,
+        compile_duration,
+        || blake_chain_guest::preprocess_prover_blake_chain(&program),
+        || blake_chain_guest::preprocess_verifier_blake_chain(&program),
+    );
 
     let prove_blake_chain = blake_chain_guest::build_prover_blake_chain(program, prover_preprocessing);
     let verify_blake_chain = blake_chain_guest::build_verifier_blake_chain(verifier_preprocessing);
@@ -465,20 +973,32 @@ fn benchmark_blake_chain(config: &BenchmarkConfig) -> BenchmarkResult {
 
     let mut verifier_durations = Vec::new();
 
-    // For the first verification, we already have the proof
+    // Serialize proof once for reuse across iterations
+    let mut proof_bytes = Vec::new();
+    proof
+        .serialize_with_mode(&mut proof_bytes, Compress::Yes)
+        .unwrap();
+
+    // For the first verification, deserialize and use the proof
     let verify_start = Instant::now();
-    let is_valid = verify_blake_chain(input, config.n, output, proof);
+    let proof_clone =
+        JoltHyperKZGProof::deserialize_with_mode(&proof_bytes[..], Compress::Yes, Validate::Yes)
+            .unwrap();
+    let is_valid = verify_blake_chain(input, config.n, output, proof_clone);
     assert!(is_valid);
     let verify_end = Instant::now();
     verifier_durations.push(verify_end.duration_since(verify_start));
 
-    // For additional iterations, we need to generate new proofs since Jolt consumes them
+    // For additional iterations, deserialize a fresh proof each time
     for _ in 1..config.verifier_iterations {
-        let (verify_output, verify_proof) = prove_blake_chain(input, config.n);
-        assert_eq!(output, verify_output);
-
         let verify_start = Instant::now();
-        let is_valid = verify_blake_chain(input, config.n, verify_output, verify_proof);
+        let proof_clone = JoltHyperKZGProof::deserialize_with_mode(
+            &proof_bytes[..],
+            Compress::Yes,
+            Validate::Yes,
+        )
+        .unwrap();
+        let is_valid = verify_blake_chain(input, config.n, output, proof_clone);
         assert!(is_valid);
         let verify_end = Instant::now();
         verifier_durations.push(verify_end.duration_since(verify_start));
@@ -489,19 +1009,78 @@ fn benchmark_blake_chain(config: &BenchmarkConfig) -> BenchmarkResult {
         prover_durations: vec![end.duration_since(start)],
         verifier_durations,
         cycle_count: trace_len,
+        preprocessing: Some(preprocessing),
+        settings: layout.settings(),
         ..Default::default()
     }
 }
 
 fn benchmark_blake(config: &BenchmarkConfig) -> BenchmarkResult {
     let target_dir = "/tmp/jolt-guest-targets";
-    let program = blake_guest::compile_blake(target_dir);
-
-    let prover_preprocessing = blake_guest::preprocess_prover_blake(&program);
-    let verifier_preprocessing = blake_guest::preprocess_verifier_blake(&program);
-
-    let prove_blake = blake_guest::build_prover_blake(program, prover_preprocessing);
-    let verify_blake = blake_guest::build_verifier_blake(verifier_preprocessing);
+    let layout = Layout::for_input(config.n as usize);
+    let large = layout == LARGE_LAYOUT;
+    eprintln!("Guest layout: {:?}", layout);
+
+    let (program, compile_duration) = compile(|| {
+        if large {
+            blake_guest::compile_blake_large(target_dir)
+        } else {
+            blake_guest::compile_blake(target_dir)
+        }
+    });
+    let analyze = || {
+        if large {
+            blake_guest::analyze_blake_large(&vec![5u8; config.n as usize])
+        } else {
+            blake_guest::analyze_blake(&vec![5u8; config.n as usize])
+        }
+    };
+    if config.mode == Mode::Execute {
+        let mut result = execute(analyze);
+        result.settings = layout.settings();
+        return result;
+    }
+    layout.check_trace_length(analyze);
+
+    let (prover_preprocessing, verifier_preprocessing, preprocessing) = preprocess(
+        config,
+        &program,
+        compile_duration,
+        || {
+            if large {
+                blake_guest::preprocess_prover_blake_large(&program)
+            } else {
+                blake_guest::preprocess_prover_blake(&program)
+            }
+        },
+        || {
+            if large {
+                blake_guest::preprocess_verifier_blake_large(&program)
+            } else {
+                blake_guest::preprocess_verifier_blake(&program)
+            }
+        },
+    );
+
+    // the functions of both layouts have the same signature
+    let prove_blake: Box<dyn Fn(&[u8]) -> ([u8; 32], JoltHyperKZGProof)> = if large {
+        Box::new(blake_guest::build_prover_blake_large(
+            program,
+            prover_preprocessing,
+        ))
+    } else {
+        Box::new(blake_guest::build_prover_blake(
+            program,
+            prover_preprocessing,
+        ))
+    };
+    let verify_blake: Box<dyn Fn(&[u8], [u8; 32], JoltHyperKZGProof) -> bool> = if large {
+        Box::new(blake_guest::build_verifier_blake_large(
+            verifier_preprocessing,
+        ))
+    } else {
+        Box::new(blake_guest::build_verifier_blake(verifier_preprocessing))
+    };
 
     let input = vec![5u8; config.n as usize];
     let input = input.as_slice();
@@ -551,6 +1130,8 @@ fn benchmark_blake(config: &BenchmarkConfig) -> BenchmarkResult {
         prover_durations: vec![end.duration_since(start)],
         verifier_durations,
         cycle_count: trace_len,
+        preprocessing: Some(preprocessing),
+        settings: layout.settings(),
         ..Default::default()
     }
 }
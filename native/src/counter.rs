@@ -0,0 +1,58 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::os::fd::{AsRawFd, FromRawFd};
+
+use perf_event_open_sys::bindings::{
+    perf_event_attr, perf_hw_id_PERF_COUNT_HW_INSTRUCTIONS, perf_type_id_PERF_TYPE_HARDWARE,
+};
+use perf_event_open_sys::{ioctls, perf_event_open};
+
+/// Counts the instructions this thread retires in user space,
+/// through the hardware counters of `perf_event_open(2)`
+pub struct InstructionCounter {
+    file: File,
+}
+
+fn check(result: i32) -> io::Result<()> {
+    if result < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+impl InstructionCounter {
+    /// Fails if the kernel does not allow it (see `perf_event_paranoid`)
+    /// or the machine has no hardware counters, e.g. in many VMs
+    pub fn new() -> io::Result<InstructionCounter> {
+        let mut attrs = perf_event_attr {
+            size: std::mem::size_of::<perf_event_attr>() as u32,
+            type_: perf_type_id_PERF_TYPE_HARDWARE,
+            config: perf_hw_id_PERF_COUNT_HW_INSTRUCTIONS as u64,
+            ..Default::default()
+        };
+        attrs.set_disabled(1);
+        attrs.set_exclude_kernel(1);
+        attrs.set_exclude_hv(1);
+
+        let fd = unsafe { perf_event_open(&mut attrs, 0, -1, -1, 0) };
+        check(fd)?;
+        Ok(InstructionCounter {
+            file: unsafe { File::from_raw_fd(fd) },
+        })
+    }
+
+    /// Run `f` and return the number of instructions it took
+    pub fn count<T>(&mut self, f: impl FnOnce() -> T) -> io::Result<(u64, T)> {
+        let fd = self.file.as_raw_fd();
+        unsafe {
+            check(ioctls::RESET(fd, 0))?;
+            check(ioctls::ENABLE(fd, 0))?;
+        }
+        let output = f();
+        unsafe { check(ioctls::DISABLE(fd, 0))? };
+
+        let mut count = [0u8; 8];
+        self.file.read_exact(&mut count)?;
+        Ok((u64::from_ne_bytes(count), output))
+    }
+}
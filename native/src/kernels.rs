@@ -0,0 +1,166 @@
+//! The workloads of the zkVM guest programs, as plain Rust on the host
+
+use std::hint::black_box;
+
+use blake2::Blake2s256;
+use k256::elliptic_curve::point::AffineCoordinates;
+use k256::{AffinePoint, ProjectivePoint};
+use sha2::{Digest, Sha256};
+use sha3::Keccak256;
+
+pub fn fib(n: u32) -> u32 {
+    let mut a: u32 = 0;
+    let mut b: u32 = 1;
+    let mut sum: u32;
+    for _ in 1..n {
+        sum = a.wrapping_add(b);
+        a = b;
+        b = sum;
+    }
+    b
+}
+
+pub fn hash<D: Digest>(input: &[u8]) -> [u8; 32] {
+    let mut hasher = D::new();
+    hasher.update(black_box(input));
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&hasher.finalize());
+    hash
+}
+
+pub fn hash_chain<D: Digest>(input: [u8; 32], num_iters: u32) -> [u8; 32] {
+    let mut hash = input;
+    for _ in 0..num_iters {
+        let mut hasher = D::new();
+        hasher.update(black_box(hash));
+        hash.copy_from_slice(&hasher.finalize());
+    }
+    hash
+}
+
+pub fn sha2(input: &[u8]) -> [u8; 32] {
+    hash::<Sha256>(input)
+}
+
+pub fn sha2_chain(input: [u8; 32], num_iters: u32) -> [u8; 32] {
+    hash_chain::<Sha256>(input, num_iters)
+}
+
+pub fn sha3(input: &[u8]) -> [u8; 32] {
+    hash::<Keccak256>(input)
+}
+
+pub fn sha3_chain(input: [u8; 32], num_iters: u32) -> [u8; 32] {
+    hash_chain::<Keccak256>(input, num_iters)
+}
+
+pub fn blake(input: &[u8]) -> [u8; 32] {
+    hash::<Blake2s256>(input)
+}
+
+pub fn blake_chain(input: [u8; 32], num_iters: u32) -> [u8; 32] {
+    hash_chain::<Blake2s256>(input, num_iters)
+}
+
+pub fn mat_mul(size: usize) -> u32 {
+    let a = vec![vec![2u32; size]; size];
+    let b = vec![vec![3u32; size]; size];
+
+    black_box(&a);
+    black_box(&b);
+
+    let mut result = vec![vec![0u32; size]; size];
+    let mut sum = 0u32;
+    for i in 0..size {
+        for j in 0..size {
+            for k in 0..size {
+                let prod = a[i][k].wrapping_mul(b[k][j]);
+                result[i][j] = result[i][j].wrapping_add(prod);
+                sum = sum.wrapping_add(prod);
+            }
+        }
+    }
+
+    sum
+}
+
+pub fn ec(n: u32) -> [u8; 32] {
+    let g = AffinePoint::GENERATOR;
+    let mut res = ProjectivePoint::from(g);
+
+    for _ in 0..n {
+        res += g;
+    }
+
+    let affine = AffinePoint::from(res);
+    affine.x().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use utils::hex;
+
+    // the values the guests commit for the same inputs
+    const DIGEST: [u8; 32] = [5u8; 32];
+
+    #[test]
+    fn test_fib() {
+        assert_eq!(fib(0), 1);
+        assert_eq!(fib(10), 55);
+        // wraps around like the guest
+        assert_eq!(fib(100), 3314859971);
+    }
+
+    #[test]
+    fn test_sha2() {
+        let digest = "f849d67325facf04177bc663b2dc544051831c589ef581d412f2eba44834e77c";
+        assert_eq!(hex(&sha2(&DIGEST)), digest);
+        assert_eq!(hex(&sha2_chain(DIGEST, 1)), digest);
+        assert_eq!(
+            hex(&sha2_chain(DIGEST, 2)),
+            "be3246b46eb9c831ad5d1827c115be0c8fd6502e81156b695a522df5a6e4e99c"
+        );
+    }
+
+    #[test]
+    fn test_sha3() {
+        let digest = "d8541d995d85cb64d51c6348e21eecd6e51cbcda5b0c5207ae87e605839e70ef";
+        assert_eq!(hex(&sha3(&DIGEST)), digest);
+        assert_eq!(hex(&sha3_chain(DIGEST, 1)), digest);
+        assert_eq!(
+            hex(&sha3_chain(DIGEST, 2)),
+            "949c3be2ec4f3b1e4b900cd975d8336b8bd90d441d2df470c787ee78bd156c9e"
+        );
+    }
+
+    #[test]
+    fn test_blake() {
+        let digest = "a8de21af385005bcb56a859ecc1dff3819167a7d21aa1a9203fe2ee32721f5b6";
+        assert_eq!(hex(&blake(&DIGEST)), digest);
+        assert_eq!(hex(&blake_chain(DIGEST, 1)), digest);
+        assert_eq!(
+            hex(&blake_chain(DIGEST, 2)),
+            "835c7858c0983b7ed9d3c6018690ef5b55ae1ac33919fd97dab8e46aa4c05f48"
+        );
+    }
+
+    #[test]
+    fn test_mat_mul() {
+        // every one of the n^3 products is 2 * 3
+        assert_eq!(mat_mul(4), 6 * 4 * 4 * 4);
+    }
+
+    #[test]
+    fn test_ec() {
+        // x coordinates of 2G and 3G on secp256k1
+        assert_eq!(
+            hex(&ec(1)),
+            "c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5"
+        );
+        assert_eq!(
+            hex(&ec(2)),
+            "f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9"
+        );
+    }
+}
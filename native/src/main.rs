@@ -0,0 +1,103 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use clap::Parser;
+use utils::{hex, BenchmarkConfig, BenchmarkResult, ExecutionReport, Mode};
+
+mod counter;
+mod kernels;
+
+use counter::InstructionCounter;
+
+// a kernel is repeated until its runs add up to at least this long
+const MIN_MEASUREMENT: Duration = Duration::from_millis(200);
+const MIN_RUNS: usize = 3;
+
+#[derive(Parser, Debug)]
+#[clap()]
+pub struct Cli {
+    #[arg(long)]
+    pub n: u32,
+
+    #[arg(long)]
+    pub program: String,
+
+    // nothing is verified natively, accepted for compatibility with the runner
+    #[arg(long, default_value = "1")]
+    pub verifier_iterations: u32,
+
+    // nothing is proven natively, the kernel is always only executed
+    #[arg(long, value_enum, default_value_t = Mode::Prove)]
+    pub mode: Mode,
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    let config = BenchmarkConfig {
+        n: cli.n,
+        program: cli.program.clone(),
+        verifier_iterations: cli.verifier_iterations,
+        mode: cli.mode,
+        ..Default::default()
+    };
+
+    let n = config.n;
+    let input = vec![5u8; n as usize];
+    let digest = [5u8; 32];
+    let result = match cli.program.as_str() {
+        "fib" => run(|| kernels::fib(n).to_le_bytes().to_vec()),
+        "sha2" => run(|| kernels::sha2(&input).to_vec()),
+        "sha2-chain" => run(|| kernels::sha2_chain(digest, n).to_vec()),
+        "sha3" => run(|| kernels::sha3(&input).to_vec()),
+        "sha3-chain" => run(|| kernels::sha3_chain(digest, n).to_vec()),
+        "mat-mul" => run(|| kernels::mat_mul(n as usize).to_le_bytes().to_vec()),
+        "ec" => run(|| kernels::ec(n).to_vec()),
+        "blake" => run(|| kernels::blake(&input).to_vec()),
+        "blake-chain" => run(|| kernels::blake_chain(digest, n).to_vec()),
+        _ => panic!("Unknown program: {}", cli.program),
+    };
+
+    std::fs::write("results.json", result.to_json()).unwrap();
+}
+
+/// Time the kernel and count the instructions it retires,
+/// the cycle count of the result is the instruction count of the host
+fn run(mut kernel: impl FnMut() -> Vec<u8>) -> BenchmarkResult {
+    // warm up caches and the allocator
+    let public_output = kernel();
+
+    let mut settings = BTreeMap::new();
+    // without hardware counters the cycle count stays 0, which readers treat as missing
+    let instructions = match InstructionCounter::new().and_then(|mut c| c.count(&mut kernel)) {
+        Ok((instructions, _)) => instructions as usize,
+        Err(e) => {
+            eprintln!("Failed to count instructions: {}", e);
+            settings.insert("instruction-count".to_string(), "unavailable".to_string());
+            0
+        }
+    };
+
+    let mut durations = Vec::new();
+    let mut total = Duration::ZERO;
+    while durations.len() < MIN_RUNS || total < MIN_MEASUREMENT {
+        let start = Instant::now();
+        std::hint::black_box(kernel());
+        let duration = start.elapsed();
+        durations.push(duration);
+        total += duration;
+    }
+    durations.sort();
+    let duration = durations[durations.len() / 2];
+
+    BenchmarkResult {
+        cycle_count: instructions,
+        execution: Some(ExecutionReport {
+            duration,
+            public_output: hex(&public_output),
+            ..Default::default()
+        }),
+        settings,
+        ..Default::default()
+    }
+}
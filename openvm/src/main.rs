@@ -1,17 +1,20 @@
 use clap::Parser;
 use std::sync::Arc;
 use std::{time::Instant, usize};
-use utils::{size, BenchmarkConfig, BenchmarkResult};
+use utils::{hex, size, BenchmarkConfig, BenchmarkResult, ExecutionReport, Mode};
 
 use openvm_algebra_circuit::ModularExtension;
 use openvm_build::GuestOptions;
+use openvm_circuit::arch::{VmConfig, VmExecutor};
+use openvm_circuit::system::memory::tree::public_values::extract_public_values;
 use openvm_ecc_circuit::{WeierstrassExtension, SECP256K1_CONFIG};
 use openvm_sdk::{
     commit::AppExecutionCommit,
     config::{AggStarkConfig, AppConfig, SdkVmConfig},
-    Sdk, StdIn,
+    Sdk, StdIn, F,
 };
 use openvm_stark_sdk::config::FriParameters;
+use openvm_stark_sdk::openvm_stark_backend::p3_field::PrimeField32;
 
 #[derive(Parser, Debug)]
 #[clap()]
@@ -24,6 +27,9 @@ pub struct Cli {
 
     #[arg(long, default_value = "1")]
     pub verifier_iterations: u32,
+
+    #[arg(long, value_enum, default_value_t = Mode::Prove)]
+    pub mode: Mode,
 }
 
 fn main() {
@@ -33,6 +39,8 @@ fn main() {
         n: cli.n,
         program: cli.program.clone(),
         verifier_iterations: cli.verifier_iterations,
+        mode: cli.mode,
+        ..Default::default()
     };
 
     let result = match cli.program.as_str() {
@@ -55,6 +63,70 @@ fn main() {
     std::fs::write("results.json", result.to_json()).unwrap();
 }
 
+fn run(
+    target_path: &str,
+    stdin: &mut StdIn,
+    vm_config: SdkVmConfig,
+    config: &BenchmarkConfig,
+) -> BenchmarkResult {
+    match config.mode {
+        Mode::Prove => prove_and_verify(target_path, stdin, vm_config, config.verifier_iterations),
+        Mode::Execute => execute(target_path, stdin, vm_config),
+    }
+}
+
+fn execute(target_path: &str, stdin: &StdIn, vm_config: SdkVmConfig) -> BenchmarkResult {
+    let sdk = Sdk::new();
+
+    let elf = sdk
+        .build(
+            GuestOptions::default(),
+            &vm_config,
+            target_path,
+            &Default::default(),
+            None,
+        )
+        .unwrap();
+    let exe = sdk.transpile(elf, vm_config.transpiler()).unwrap();
+
+    // execute split into segments, as the prover would, so the same run gives the cycles
+    let executor = VmExecutor::<F, _>::new(vm_config);
+    let start = Instant::now();
+    let segments = executor.execute_segments(exe, stdin.clone()).unwrap();
+    let duration = start.elapsed();
+
+    let cycle_count = segments
+        .iter()
+        .map(|segment| segment.metrics.cycle_count)
+        .sum();
+    let system = executor.config.system();
+    let public_values = extract_public_values(
+        &system.memory_config.memory_dimensions(),
+        system.num_public_values,
+        segments
+            .last()
+            .and_then(|segment| segment.final_memory.as_ref())
+            .unwrap(),
+    );
+
+    // public values are revealed byte by byte
+    let public_output: Vec<u8> = public_values
+        .iter()
+        .map(|value| value.as_canonical_u32() as u8)
+        .collect();
+
+    BenchmarkResult {
+        cycle_count,
+        execution: Some(ExecutionReport {
+            duration,
+            segments: Some(segments.len()),
+            public_output: hex(&public_output),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
 fn prove_and_verify(
     target_path: &str,
     stdin: &mut StdIn,
@@ -151,12 +223,7 @@ fn benchmark_fib(config: &BenchmarkConfig) -> BenchmarkResult {
         .io(Default::default())
         .build();
 
-    prove_and_verify(
-        target_path,
-        &mut stdin,
-        vm_config,
-        config.verifier_iterations,
-    )
+    run(target_path, &mut stdin, vm_config, config)
 }
 
 fn benchmark_sha2(config: &BenchmarkConfig) -> BenchmarkResult {
@@ -173,12 +240,7 @@ fn benchmark_sha2(config: &BenchmarkConfig) -> BenchmarkResult {
         .io(Default::default())
         .build();
 
-    prove_and_verify(
-        target_path,
-        &mut stdin,
-        vm_config,
-        config.verifier_iterations,
-    )
+    run(target_path, &mut stdin, vm_config, config)
 }
 
 fn benchmark_sha2_precompile(config: &BenchmarkConfig) -> BenchmarkResult {
@@ -196,12 +258,7 @@ fn benchmark_sha2_precompile(config: &BenchmarkConfig) -> BenchmarkResult {
         .sha256(Default::default())
         .build();
 
-    prove_and_verify(
-        target_path,
-        &mut stdin,
-        vm_config,
-        config.verifier_iterations,
-    )
+    run(target_path, &mut stdin, vm_config, config)
 }
 
 fn benchmark_sha2_chain(config: &BenchmarkConfig) -> BenchmarkResult {
@@ -219,12 +276,7 @@ fn benchmark_sha2_chain(config: &BenchmarkConfig) -> BenchmarkResult {
         .io(Default::default())
         .build();
 
-    prove_and_verify(
-        target_path,
-        &mut stdin,
-        vm_config,
-        config.verifier_iterations,
-    )
+    run(target_path, &mut stdin, vm_config, config)
 }
 
 fn benchmark_sha2_chain_precompile(config: &BenchmarkConfig) -> BenchmarkResult {
@@ -243,12 +295,7 @@ fn benchmark_sha2_chain_precompile(config: &BenchmarkConfig) -> BenchmarkResult
         .sha256(Default::default())
         .build();
 
-    prove_and_verify(
-        target_path,
-        &mut stdin,
-        vm_config,
-        config.verifier_iterations,
-    )
+    run(target_path, &mut stdin, vm_config, config)
 }
 
 fn benchmark_sha3(config: &BenchmarkConfig) -> BenchmarkResult {
@@ -265,12 +312,7 @@ fn benchmark_sha3(config: &BenchmarkConfig) -> BenchmarkResult {
         .io(Default::default())
         .build();
 
-    prove_and_verify(
-        target_path,
-        &mut stdin,
-        vm_config,
-        config.verifier_iterations,
-    )
+    run(target_path, &mut stdin, vm_config, config)
 }
 
 fn benchmark_sha3_precompile(config: &BenchmarkConfig) -> BenchmarkResult {
@@ -288,12 +330,7 @@ fn benchmark_sha3_precompile(config: &BenchmarkConfig) -> BenchmarkResult {
         .keccak(Default::default())
         .build();
 
-    prove_and_verify(
-        target_path,
-        &mut stdin,
-        vm_config,
-        config.verifier_iterations,
-    )
+    run(target_path, &mut stdin, vm_config, config)
 }
 
 fn benchmark_sha3_chain(config: &BenchmarkConfig) -> BenchmarkResult {
@@ -311,12 +348,7 @@ fn benchmark_sha3_chain(config: &BenchmarkConfig) -> BenchmarkResult {
         .io(Default::default())
         .build();
 
-    prove_and_verify(
-        target_path,
-        &mut stdin,
-        vm_config,
-        config.verifier_iterations,
-    )
+    run(target_path, &mut stdin, vm_config, config)
 }
 
 fn benchmark_sha3_chain_precompile(config: &BenchmarkConfig) -> BenchmarkResult {
@@ -335,12 +367,7 @@ fn benchmark_sha3_chain_precompile(config: &BenchmarkConfig) -> BenchmarkResult
         .keccak(Default::default())
         .build();
 
-    prove_and_verify(
-        target_path,
-        &mut stdin,
-        vm_config,
-        config.verifier_iterations,
-    )
+    run(target_path, &mut stdin, vm_config, config)
 }
 
 fn benchmark_mat_mul(config: &BenchmarkConfig) -> BenchmarkResult {
@@ -356,12 +383,7 @@ fn benchmark_mat_mul(config: &BenchmarkConfig) -> BenchmarkResult {
         .io(Default::default())
         .build();
 
-    prove_and_verify(
-        target_path,
-        &mut stdin,
-        vm_config,
-        config.verifier_iterations,
-    )
+    run(target_path, &mut stdin, vm_config, config)
 }
 
 fn benchmark_ec(config: &BenchmarkConfig) -> BenchmarkResult {
@@ -377,12 +399,7 @@ fn benchmark_ec(config: &BenchmarkConfig) -> BenchmarkResult {
         .io(Default::default())
         .build();
 
-    prove_and_verify(
-        target_path,
-        &mut stdin,
-        vm_config,
-        config.verifier_iterations,
-    )
+    run(target_path, &mut stdin, vm_config, config)
 }
 
 fn benchmark_ec_precompile(config: &BenchmarkConfig) -> BenchmarkResult {
@@ -403,12 +420,7 @@ fn benchmark_ec_precompile(config: &BenchmarkConfig) -> BenchmarkResult {
         .ecc(WeierstrassExtension::new(vec![SECP256K1_CONFIG.clone()]))
         .build();
 
-    prove_and_verify(
-        target_path,
-        &mut stdin,
-        vm_config,
-        config.verifier_iterations,
-    )
+    run(target_path, &mut stdin, vm_config, config)
 }
 
 fn benchmark_blake(config: &BenchmarkConfig) -> BenchmarkResult {
@@ -425,12 +437,7 @@ fn benchmark_blake(config: &BenchmarkConfig) -> BenchmarkResult {
         .io(Default::default())
         .build();
 
-    prove_and_verify(
-        target_path,
-        &mut stdin,
-        vm_config,
-        config.verifier_iterations,
-    )
+    run(target_path, &mut stdin, vm_config, config)
 }
 
 fn benchmark_blake_chain(config: &BenchmarkConfig) -> BenchmarkResult {
@@ -448,10 +455,5 @@ fn benchmark_blake_chain(config: &BenchmarkConfig) -> BenchmarkResult {
         .io(Default::default())
         .build();
 
-    prove_and_verify(
-        target_path,
-        &mut stdin,
-        vm_config,
-        config.verifier_iterations,
-    )
+    run(target_path, &mut stdin, vm_config, config)
 }
@@ -1,3 +0,0 @@
-fn main() {
-    risc0_build::embed_methods();
-}
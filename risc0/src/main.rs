@@ -0,0 +1,308 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use clap::{Parser, ValueEnum};
+use methods::{
+    BLAKE_CHAIN_GUEST_ELF, BLAKE_CHAIN_GUEST_ID, BLAKE_GUEST_ELF, BLAKE_GUEST_ID, EC_GUEST_ELF,
+    EC_GUEST_ID, EC_PRECOMPILE_GUEST_ELF, EC_PRECOMPILE_GUEST_ID, FIB_GUEST_ELF, FIB_GUEST_ID,
+    MAT_MUL_GUEST_ELF, MAT_MUL_GUEST_ID, SHA2_CHAIN_GUEST_ELF, SHA2_CHAIN_GUEST_ID,
+    SHA2_CHAIN_PRECOMPILE_GUEST_ELF, SHA2_CHAIN_PRECOMPILE_GUEST_ID, SHA2_GUEST_ELF, SHA2_GUEST_ID,
+    SHA2_PRECOMPILE_GUEST_ELF, SHA2_PRECOMPILE_GUEST_ID, SHA3_CHAIN_GUEST_ELF, SHA3_CHAIN_GUEST_ID,
+    SHA3_CHAIN_PRECOMPILE_GUEST_ELF, SHA3_CHAIN_PRECOMPILE_GUEST_ID, SHA3_GUEST_ELF, SHA3_GUEST_ID,
+    SHA3_PRECOMPILE_GUEST_ELF, SHA3_PRECOMPILE_GUEST_ID,
+};
+use risc0_zkvm::{default_executor, default_prover, ExecutorEnv, Journal, ProverOpts, SessionInfo};
+use serde::de::DeserializeOwned;
+use utils::{hex, size, BenchmarkConfig, BenchmarkResult, ExecutionReport, Mode, WrapReport};
+
+/// Which receipt the prover produces, each is compressed from the one before
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReceiptKind {
+    // one STARK receipt per segment
+    Composite,
+    // the segment receipts recursively compressed into a single STARK receipt
+    Succinct,
+    // the succinct receipt wrapped into a Groth16 SNARK by the local risc0-groth16 prover
+    Groth16,
+}
+
+impl fmt::Display for ReceiptKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ReceiptKind::Composite => "composite",
+            ReceiptKind::Succinct => "succinct",
+            ReceiptKind::Groth16 => "groth16",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Parser, Debug)]
+#[clap()]
+pub struct Cli {
+    #[arg(long)]
+    pub n: u32,
+
+    #[arg(long)]
+    pub program: String,
+
+    #[arg(long, default_value = "1")]
+    pub verifier_iterations: u32,
+
+    #[arg(long, value_enum, default_value_t = Mode::Prove)]
+    pub mode: Mode,
+
+    #[arg(long, value_enum, default_value_t = ReceiptKind::Succinct)]
+    pub receipt_kind: ReceiptKind,
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    let config = BenchmarkConfig {
+        n: cli.n,
+        program: cli.program.clone(),
+        verifier_iterations: cli.verifier_iterations,
+        mode: cli.mode,
+        ..Default::default()
+    };
+    let receipt_kind = cli.receipt_kind;
+
+    let mut result = match cli.program.as_str() {
+        "fib" => run::<u32>(n_input, FIB_GUEST_ELF, FIB_GUEST_ID, &config, receipt_kind),
+        "sha2" => run::<[u8; 32]>(
+            bytes_input,
+            SHA2_GUEST_ELF,
+            SHA2_GUEST_ID,
+            &config,
+            receipt_kind,
+        ),
+        "sha2-precompile" => run::<[u8; 32]>(
+            bytes_input,
+            SHA2_PRECOMPILE_GUEST_ELF,
+            SHA2_PRECOMPILE_GUEST_ID,
+            &config,
+            receipt_kind,
+        ),
+        "sha2-chain" => run::<[u8; 32]>(
+            chain_input,
+            SHA2_CHAIN_GUEST_ELF,
+            SHA2_CHAIN_GUEST_ID,
+            &config,
+            receipt_kind,
+        ),
+        "sha2-chain-precompile" => run::<[u8; 32]>(
+            chain_input,
+            SHA2_CHAIN_PRECOMPILE_GUEST_ELF,
+            SHA2_CHAIN_PRECOMPILE_GUEST_ID,
+            &config,
+            receipt_kind,
+        ),
+        "sha3" => run::<[u8; 32]>(
+            bytes_input,
+            SHA3_GUEST_ELF,
+            SHA3_GUEST_ID,
+            &config,
+            receipt_kind,
+        ),
+        "sha3-precompile" => run::<[u8; 32]>(
+            bytes_input,
+            SHA3_PRECOMPILE_GUEST_ELF,
+            SHA3_PRECOMPILE_GUEST_ID,
+            &config,
+            receipt_kind,
+        ),
+        "sha3-chain" => run::<[u8; 32]>(
+            chain_input,
+            SHA3_CHAIN_GUEST_ELF,
+            SHA3_CHAIN_GUEST_ID,
+            &config,
+            receipt_kind,
+        ),
+        "sha3-chain-precompile" => run::<[u8; 32]>(
+            chain_input,
+            SHA3_CHAIN_PRECOMPILE_GUEST_ELF,
+            SHA3_CHAIN_PRECOMPILE_GUEST_ID,
+            &config,
+            receipt_kind,
+        ),
+        "mat-mul" => run::<u32>(
+            n_input,
+            MAT_MUL_GUEST_ELF,
+            MAT_MUL_GUEST_ID,
+            &config,
+            receipt_kind,
+        ),
+        "ec" => run::<[u8; 32]>(n_input, EC_GUEST_ELF, EC_GUEST_ID, &config, receipt_kind),
+        "ec-precompile" => run::<[u8; 32]>(
+            n_input,
+            EC_PRECOMPILE_GUEST_ELF,
+            EC_PRECOMPILE_GUEST_ID,
+            &config,
+            receipt_kind,
+        ),
+        "blake" => run::<[u8; 32]>(
+            bytes_input,
+            BLAKE_GUEST_ELF,
+            BLAKE_GUEST_ID,
+            &config,
+            receipt_kind,
+        ),
+        "blake-chain" => run::<[u8; 32]>(
+            chain_input,
+            BLAKE_CHAIN_GUEST_ELF,
+            BLAKE_CHAIN_GUEST_ID,
+            &config,
+            receipt_kind,
+        ),
+        _ => panic!("Unknown program: {}", cli.program),
+    };
+    if config.mode == Mode::Prove {
+        result
+            .settings
+            .insert("receipt-kind".into(), cli.receipt_kind.to_string());
+    }
+
+    std::fs::write("results.json", result.to_json()).unwrap();
+}
+
+/// Input of fib, mat-mul and ec: just n
+fn n_input(config: &BenchmarkConfig) -> ExecutorEnv<'static> {
+    ExecutorEnv::builder()
+        .write(&config.n)
+        .unwrap()
+        .build()
+        .unwrap()
+}
+
+/// Input of the hash benchmarks: n bytes to hash
+fn bytes_input(config: &BenchmarkConfig) -> ExecutorEnv<'static> {
+    let input = vec![5u8; config.n as usize];
+    ExecutorEnv::builder()
+        .write(&input)
+        .unwrap()
+        .build()
+        .unwrap()
+}
+
+/// Input of the hash chain benchmarks: a digest to hash n times
+fn chain_input(config: &BenchmarkConfig) -> ExecutorEnv<'static> {
+    let input = [5u8; 32];
+    ExecutorEnv::builder()
+        .write(&input)
+        .unwrap()
+        .write(&config.n)
+        .unwrap()
+        .build()
+        .unwrap()
+}
+
+fn run<T: DeserializeOwned>(
+    input: fn(&BenchmarkConfig) -> ExecutorEnv<'static>,
+    elf: &[u8],
+    image_id: [u32; 8],
+    config: &BenchmarkConfig,
+    receipt_kind: ReceiptKind,
+) -> BenchmarkResult {
+    // the cycles are counted by the executor in both modes,
+    // so that they are the same quantity whether proving or not
+    let start = Instant::now();
+    let session = default_executor().execute(input(config), elf).unwrap();
+    let duration = start.elapsed();
+    decode_output::<T>(&session.journal);
+
+    let mut result = match config.mode {
+        Mode::Prove => prove_and_verify::<T>(
+            input(config),
+            elf,
+            image_id,
+            config.verifier_iterations,
+            receipt_kind,
+        ),
+        Mode::Execute => BenchmarkResult::default(),
+    };
+    result.cycle_count = session
+        .segments
+        .iter()
+        .map(|segment| segment.cycles as usize)
+        .sum();
+    result.execution = Some(execution_report(&session, duration));
+    result
+}
+
+/// Fail if the guest did not commit an output of the expected type
+fn decode_output<T: DeserializeOwned>(journal: &Journal) -> T {
+    journal
+        .decode()
+        .expect("the journal does not hold the output of the guest")
+}
+
+fn prove_and_verify<T: DeserializeOwned>(
+    env: ExecutorEnv,
+    elf: &[u8],
+    image_id: [u32; 8],
+    verifier_iterations: u32,
+    receipt_kind: ReceiptKind,
+) -> BenchmarkResult {
+    let prover = default_prover();
+    let opts = match receipt_kind {
+        ReceiptKind::Composite => ProverOpts::composite(),
+        ReceiptKind::Succinct | ReceiptKind::Groth16 => ProverOpts::succinct(),
+    };
+
+    let prover_start = Instant::now();
+    let prove_info = prover.prove_with_opts(env, elf, &opts).unwrap();
+    let mut prover_duration = prover_start.elapsed();
+
+    let mut receipt = prove_info.receipt;
+    decode_output::<T>(&receipt.journal);
+
+    // the Groth16 receipt is compressed from the succinct one,
+    // which separates the wrap time from the STARK proving
+    let mut wrap = None;
+    if receipt_kind == ReceiptKind::Groth16 {
+        let compressed_proof_size = size(&receipt);
+        let wrap_start = Instant::now();
+        receipt = prover.compress(&ProverOpts::groth16(), &receipt).unwrap();
+        let wrap_duration = wrap_start.elapsed();
+        prover_duration += wrap_duration;
+        wrap = Some(WrapReport {
+            duration: wrap_duration,
+            compressed_proof_size,
+        });
+    }
+    let proof_size = size(&receipt);
+
+    let mut verifier_durations = Vec::new();
+    for _ in 0..verifier_iterations {
+        let verifier_start = Instant::now();
+        receipt.verify(image_id).unwrap();
+        verifier_durations.push(verifier_start.elapsed());
+    }
+
+    BenchmarkResult {
+        proof_size,
+        prover_durations: vec![prover_duration],
+        verifier_durations,
+        wrap,
+        ..Default::default()
+    }
+}
+
+/// What the executor reports about a run, kept in both modes
+fn execution_report(session: &SessionInfo, duration: Duration) -> ExecutionReport {
+    ExecutionReport {
+        duration,
+        // every segment is padded to a power of two
+        trace_length: Some(
+            session
+                .segments
+                .iter()
+                .map(|segment| 1u64 << segment.po2)
+                .sum(),
+        ),
+        segments: Some(session.segments.len()),
+        public_output: hex(&session.journal.bytes),
+        ..Default::default()
+    }
+}
@@ -16,6 +16,18 @@ def load_json_data(file_path):
         with open(file_path, 'r') as f:
             data = json.load(f)
         
+        if "skipped" in data["result"]:
+            # a smaller n of the same benchmark already ran out of memory or time
+            placeholder = {
+                "n": data["config"]["n"],
+                "prover time(s)": "exceeds limits",
+                "verifier time(ms)": "exceeds limits",
+                "proof size(kb)": "exceeds limits",
+                "cycle count": "exceeds limits",
+                "peak memory(gb)": "exceeds limits"
+            }
+            return (False, placeholder)
+
         if "success" not in data["result"]:
             n_value = data["config"]["n"]
             failure_data = data["result"]["failure"]
@@ -261,7 +273,9 @@ def get_tables(bench_tuple):
             
             # Handle failure signals
             for idx, value in enumerate(df_processed[col]):
-                if isinstance(df_original.iloc[idx][col], dict) and "signal" in str(df_original.iloc[idx][col]):
+                if isinstance(df_original.iloc[idx][col], dict) and "timeout" in str(df_original.iloc[idx][col]):
+                    df_processed.iloc[idx, df_processed.columns.get_loc(col)] = "⏱️"
+                elif isinstance(df_original.iloc[idx][col], dict) and "signal" in str(df_original.iloc[idx][col]):
                     # Extract signal from failure data
                     try:
                         if "signal" in str(value) and "9" in str(value):
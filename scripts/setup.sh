@@ -46,6 +46,8 @@ rustup target add riscv32i-unknown-none-elf
 curl -L https://risczero.com/install | bash
 export PATH="$HOME/.risc0/bin:$PATH"
 rzup install
+# prover of Groth16 receipts, proves locally without Docker
+rzup install risc0-groth16
 
 # Install SP1
 curl -L https://sp1.succinct.xyz | bash
@@ -1,7 +1,13 @@
-use std::time::Instant;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::{Duration, Instant};
 
-use sp1_sdk::{include_elf, utils as sp1_utils, ProverClient, SP1Stdin};
-use utils::{size, BenchmarkConfig, BenchmarkResult};
+use sp1_sdk::install::try_install_circuit_artifacts;
+use sp1_sdk::{
+    include_elf, utils as sp1_utils, Prover, ProverClient, SP1Proof, SP1ProverOpts,
+    SP1PublicValues, SP1Stdin,
+};
+use utils::{hex, size, BenchmarkConfig, BenchmarkResult, ExecutionReport, Mode, WrapReport};
 
 const FIBONACCI_ELF: &[u8] = include_elf!("fib");
 const SHA2_ELF: &[u8] = include_elf!("sha2");
@@ -18,7 +24,32 @@ const ECADD_PRECOMPILE_ELF: &[u8] = include_elf!("ec-precompile");
 const BLAKE_ELF: &[u8] = include_elf!("blake");
 const BLAKE_CHAIN_ELF: &[u8] = include_elf!("blake-chain");
 
-use clap::Parser;
+use clap::{Parser, ValueEnum};
+
+/// Which proof the prover produces, each is wrapped from the one before
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProofMode {
+    // one STARK proof per shard
+    Core,
+    // the shard proofs recursively compressed into a single STARK proof
+    Compressed,
+    // the compressed proof wrapped into a Groth16 SNARK, proven by the native gnark build
+    Groth16,
+    // the compressed proof wrapped into a PLONK SNARK, proven by the native gnark build
+    Plonk,
+}
+
+impl fmt::Display for ProofMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ProofMode::Core => "core",
+            ProofMode::Compressed => "compressed",
+            ProofMode::Groth16 => "groth16",
+            ProofMode::Plonk => "plonk",
+        };
+        write!(f, "{}", name)
+    }
+}
 
 #[derive(Parser, Debug)]
 #[clap()]
@@ -31,54 +62,239 @@ pub struct Cli {
     
     #[arg(long, default_value = "1")]
     pub verifier_iterations: u32,
+
+    #[arg(long, value_enum, default_value_t = Mode::Prove)]
+    pub mode: Mode,
+
+    #[arg(long, value_enum, default_value_t = ProofMode::Compressed)]
+    pub proof_mode: ProofMode,
+
+    // cycles per shard, a power of two (SP1 picks one for the memory of the machine by default)
+    #[arg(long)]
+    pub shard_size: Option<u64>,
+
+    // shards whose traces are generated and committed to at once
+    #[arg(long)]
+    pub shard_batch_size: Option<u64>,
+
+    // recompute trace commitments instead of keeping them in memory
+    #[arg(long)]
+    pub reconstruct_commitments: Option<bool>,
+}
+
+impl Cli {
+    /// `SP1ProverOpts` are read from the environment when the prover client is created,
+    /// so set the tuning options there and return them as settings of the result
+    fn apply_prover_opts(&self) -> BTreeMap<String, String> {
+        let opts = [
+            (
+                "shard-size",
+                "SHARD_SIZE",
+                self.shard_size.map(|v| v.to_string()),
+            ),
+            (
+                "shard-batch-size",
+                "SHARD_BATCH_SIZE",
+                self.shard_batch_size.map(|v| v.to_string()),
+            ),
+            (
+                "reconstruct-commitments",
+                "RECONSTRUCT_COMMITMENTS",
+                self.reconstruct_commitments.map(|v| v.to_string()),
+            ),
+        ];
+        let mut settings = BTreeMap::new();
+        for (name, var, value) in opts {
+            let value = match value {
+                Some(value) => {
+                    std::env::set_var(var, &value);
+                    value
+                }
+                // set by hand outside the runner, or left to SP1
+                None => std::env::var(var).unwrap_or_else(|_| "auto".to_string()),
+            };
+            settings.insert(name.to_string(), value);
+        }
+        settings
+    }
+}
+
+/// The prover options for the stages the driver runs by hand, with the
+/// tuning options of `apply_prover_opts` that the prover client reads as well
+fn tuned_prover_opts() -> SP1ProverOpts {
+    fn var<T: std::str::FromStr>(name: &str) -> Option<T> {
+        std::env::var(name).ok()?.parse().ok()
+    }
+
+    let mut opts = SP1ProverOpts::auto();
+    if let Some(shard_size) = var("SHARD_SIZE") {
+        opts.core_opts.shard_size = shard_size;
+    }
+    if let Some(shard_batch_size) = var("SHARD_BATCH_SIZE") {
+        opts.core_opts.shard_batch_size = shard_batch_size;
+    }
+    if let Some(reconstruct_commitments) = var("RECONSTRUCT_COMMITMENTS") {
+        opts.core_opts.reconstruct_commitments = reconstruct_commitments;
+    }
+    opts
 }
 
 fn main() {
     sp1_utils::setup_logger();
 
     let cli = Cli::parse();
+    let prover_opts = cli.apply_prover_opts();
     
     let config = BenchmarkConfig {
         n: cli.n,
         program: cli.program.clone(),
         verifier_iterations: cli.verifier_iterations,
+        mode: cli.mode,
+        ..Default::default()
     };
+    let proof_mode = cli.proof_mode;
 
-    let result = match cli.program.as_str() {
-        "fib" => bench_fibonacci(&config),
-        "sha2" => benchmark_sha2(&config),
-        "sha2-precompile" => benchmark_sha2_precompile(&config),
-        "sha2-chain" => benchmark_sha2_chain(&config),
-        "sha2-chain-precompile" => benchmark_sha2_chain_precompile(&config),
-        "sha3" => benchmark_sha3(&config),
-        "sha3-precompile" => benchmark_sha3_precompile(&config),
-        "sha3-chain" => benchmark_sha3_chain(&config),
-        "sha3-chain-precompile" => benchmark_sha3_chain_precompile(&config),
-        "mat-mul" => bench_mat_mul(&config),
-        "ec" => bench_ecadd(&config),
-        "ec-precompile" => bench_ecadd_precompile(&config),
-        "blake" => bench_blake(&config),
-        "blake-chain" => bench_blake_chain(&config),
+    let mut result = match cli.program.as_str() {
+        "fib" => bench_fibonacci(&config, proof_mode),
+        "sha2" => benchmark_sha2(&config, proof_mode),
+        "sha2-precompile" => benchmark_sha2_precompile(&config, proof_mode),
+        "sha2-chain" => benchmark_sha2_chain(&config, proof_mode),
+        "sha2-chain-precompile" => benchmark_sha2_chain_precompile(&config, proof_mode),
+        "sha3" => benchmark_sha3(&config, proof_mode),
+        "sha3-precompile" => benchmark_sha3_precompile(&config, proof_mode),
+        "sha3-chain" => benchmark_sha3_chain(&config, proof_mode),
+        "sha3-chain-precompile" => benchmark_sha3_chain_precompile(&config, proof_mode),
+        "mat-mul" => bench_mat_mul(&config, proof_mode),
+        "ec" => bench_ecadd(&config, proof_mode),
+        "ec-precompile" => bench_ecadd_precompile(&config, proof_mode),
+        "blake" => bench_blake(&config, proof_mode),
+        "blake-chain" => bench_blake_chain(&config, proof_mode),
         _ => unreachable!(),
     };
+    if config.mode == Mode::Prove {
+        result
+            .settings
+            .insert("proof-mode".into(), cli.proof_mode.to_string());
+        result.settings.extend(prover_opts);
+    }
     
     std::fs::write("results.json", result.to_json()).unwrap();
 }
 
+fn run(
+    stdin: &mut SP1Stdin,
+    elf: &[u8],
+    config: &BenchmarkConfig,
+    proof_mode: ProofMode,
+) -> BenchmarkResult {
+    match config.mode {
+        Mode::Prove => prove_and_verify(stdin, elf, config.verifier_iterations, proof_mode),
+        Mode::Execute => execute(stdin, elf),
+    }
+}
+
+fn execute(stdin: &SP1Stdin, elf: &[u8]) -> BenchmarkResult {
+    let client = ProverClient::from_env();
+
+    let start = Instant::now();
+    let (public_values, report) = client.execute(elf, stdin).run().unwrap();
+    let duration = start.elapsed();
+
+    BenchmarkResult {
+        cycle_count: report.total_instruction_count() as usize,
+        execution: Some(execution_report(&report, duration, &public_values)),
+        ..Default::default()
+    }
+}
+
+/// What the executor reports about a run, kept in both modes
+fn execution_report(
+    report: &sp1_sdk::ExecutionReport,
+    duration: Duration,
+    public_values: &SP1PublicValues,
+) -> ExecutionReport {
+    // opcodes and syscalls the program never reached are left out
+    let opcode_counts = report
+        .opcode_counts
+        .iter()
+        .filter(|(_, count)| **count > 0)
+        .map(|(opcode, count)| (format!("{:?}", opcode), *count))
+        .collect();
+    let syscall_counts = report
+        .syscall_counts
+        .iter()
+        .filter(|(_, count)| **count > 0)
+        .map(|(syscall, count)| (format!("{:?}", syscall), *count))
+        .collect();
+
+    ExecutionReport {
+        duration,
+        public_output: hex(public_values.as_slice()),
+        opcode_counts,
+        syscall_counts,
+        touched_memory_addresses: Some(report.touched_memory_addresses),
+        gas: report.gas,
+        ..Default::default()
+    }
+}
+
 fn prove_and_verify(
     stdin: &mut SP1Stdin,
     elf: &[u8],
     verifier_iterations: u32,
+    proof_mode: ProofMode,
 ) -> BenchmarkResult {
     let client = ProverClient::from_env();
-    let (_, report) = client.execute(elf, &stdin).run().unwrap();
+    let start = Instant::now();
+    let (public_values, report) = client.execute(elf, &stdin).run().unwrap();
+    let mut execution = execution_report(&report, start.elapsed(), &public_values);
     let cycle_count = report.total_instruction_count() as usize;
     let (pk, vk) = client.setup(elf);
 
+    // the SNARKs wrap a compressed proof, which is proven on its own first
+    let prover = client.prove(&pk, &stdin);
     let start = Instant::now();
-    let proof = client.prove(&pk, &stdin).compressed().run().unwrap();
-    let end = Instant::now();
-    let prover_duration = end.duration_since(start);
+    let mut proof = match proof_mode {
+        ProofMode::Core => prover.core(),
+        ProofMode::Compressed | ProofMode::Groth16 | ProofMode::Plonk => prover.compressed(),
+    }
+    .run()
+    .unwrap();
+    let mut prover_duration = start.elapsed();
+
+    // only a core proof tells how many shards the execution was split into
+    if let SP1Proof::Core(shards) = &proof.proof {
+        execution.segments = Some(shards.len());
+    }
+
+    // wrap as the SDK does, but time it apart from proving the compressed proof
+    let mut wrap = None;
+    if let ProofMode::Groth16 | ProofMode::Plonk = proof_mode {
+        let compressed_proof_size = size(&proof);
+        let SP1Proof::Compressed(reduced) = &proof.proof else {
+            unreachable!("a compressed proof was requested");
+        };
+        // the circuit artifacts are only downloaded by the first run
+        let artifacts = try_install_circuit_artifacts(&proof_mode.to_string());
+        let inner = client.inner();
+        let opts = tuned_prover_opts();
+
+        let start = Instant::now();
+        let shrunk = inner.shrink(*reduced.clone(), opts).unwrap();
+        let outer = inner.wrap_bn254(shrunk, opts).unwrap();
+        proof.proof = match proof_mode {
+            ProofMode::Groth16 => SP1Proof::Groth16(inner.wrap_groth16_bn254(outer, &artifacts)),
+            ProofMode::Plonk => SP1Proof::Plonk(inner.wrap_plonk_bn254(outer, &artifacts)),
+            ProofMode::Core | ProofMode::Compressed => unreachable!(),
+        };
+        let wrap_duration = start.elapsed();
+
+        prover_duration += wrap_duration;
+        wrap = Some(WrapReport {
+            duration: wrap_duration,
+            compressed_proof_size,
+        });
+    }
     let proof_size = size(&proof);
 
     let mut verifier_durations = Vec::new();
@@ -94,105 +310,113 @@ fn prove_and_verify(
         prover_durations: vec![prover_duration],
         verifier_durations,
         cycle_count,
+        execution: Some(execution),
+        wrap,
         ..Default::default()
     }
 }
 
-fn benchmark_sha2_chain(config: &BenchmarkConfig) -> BenchmarkResult {
+fn benchmark_sha2_chain(config: &BenchmarkConfig, proof_mode: ProofMode) -> BenchmarkResult {
     let mut stdin = SP1Stdin::new();
     let input = [5u8; 32];
     stdin.write(&input);
     stdin.write(&config.n);
-    prove_and_verify(&mut stdin, SHA2_CHAIN_ELF, config.verifier_iterations)
+    run(&mut stdin, SHA2_CHAIN_ELF, config, proof_mode)
 }
 
-fn benchmark_sha2_chain_precompile(config: &BenchmarkConfig) -> BenchmarkResult {
+fn benchmark_sha2_chain_precompile(
+    config: &BenchmarkConfig,
+    proof_mode: ProofMode,
+) -> BenchmarkResult {
     let mut stdin = SP1Stdin::new();
     let input = [5u8; 32];
     stdin.write(&input);
     stdin.write(&config.n);
-    prove_and_verify(&mut stdin, SHA2_CHAIN_PRECOMPILE_ELF, config.verifier_iterations)
+    run(&mut stdin, SHA2_CHAIN_PRECOMPILE_ELF, config, proof_mode)
 }
 
-fn benchmark_sha3_chain(config: &BenchmarkConfig) -> BenchmarkResult {
+fn benchmark_sha3_chain(config: &BenchmarkConfig, proof_mode: ProofMode) -> BenchmarkResult {
     let mut stdin = SP1Stdin::new();
     let input = [5u8; 32];
     stdin.write(&input);
     stdin.write(&config.n);
-    prove_and_verify(&mut stdin, SHA3_CHAIN_ELF, config.verifier_iterations)
+    run(&mut stdin, SHA3_CHAIN_ELF, config, proof_mode)
 }
 
-fn benchmark_sha3_chain_precompile(config: &BenchmarkConfig) -> BenchmarkResult {
+fn benchmark_sha3_chain_precompile(
+    config: &BenchmarkConfig,
+    proof_mode: ProofMode,
+) -> BenchmarkResult {
     let mut stdin = SP1Stdin::new();
     let input = [5u8; 32];
     stdin.write(&input);
     stdin.write(&config.n);
-    prove_and_verify(&mut stdin, SHA3_CHAIN_PRECOMPILE_ELF, config.verifier_iterations)
+    run(&mut stdin, SHA3_CHAIN_PRECOMPILE_ELF, config, proof_mode)
 }
 
-fn benchmark_sha2(config: &BenchmarkConfig) -> BenchmarkResult {
+fn benchmark_sha2(config: &BenchmarkConfig, proof_mode: ProofMode) -> BenchmarkResult {
     let mut stdin = SP1Stdin::new();
     let input = vec![5u8; config.n as usize];
     stdin.write(&input);
-    prove_and_verify(&mut stdin, SHA2_ELF, config.verifier_iterations)
+    run(&mut stdin, SHA2_ELF, config, proof_mode)
 }
 
-fn benchmark_sha2_precompile(config: &BenchmarkConfig) -> BenchmarkResult {
+fn benchmark_sha2_precompile(config: &BenchmarkConfig, proof_mode: ProofMode) -> BenchmarkResult {
     let mut stdin = SP1Stdin::new();
     let input = vec![5u8; config.n as usize];
     stdin.write(&input);
-    prove_and_verify(&mut stdin, SHA2_PRECOMPILE_ELF, config.verifier_iterations)
+    run(&mut stdin, SHA2_PRECOMPILE_ELF, config, proof_mode)
 }
 
-fn benchmark_sha3_precompile(config: &BenchmarkConfig) -> BenchmarkResult {
+fn benchmark_sha3_precompile(config: &BenchmarkConfig, proof_mode: ProofMode) -> BenchmarkResult {
     let mut stdin = SP1Stdin::new();
     let input = vec![5u8; config.n as usize];
     stdin.write(&input);
-    prove_and_verify(&mut stdin, SHA3_PRECOMPILE_ELF, config.verifier_iterations)
+    run(&mut stdin, SHA3_PRECOMPILE_ELF, config, proof_mode)
 }
 
-fn benchmark_sha3(config: &BenchmarkConfig) -> BenchmarkResult {
+fn benchmark_sha3(config: &BenchmarkConfig, proof_mode: ProofMode) -> BenchmarkResult {
     let mut stdin = SP1Stdin::new();
     let input = vec![5u8; config.n as usize];
     stdin.write(&input);
-    prove_and_verify(&mut stdin, SHA3_ELF, config.verifier_iterations)
+    run(&mut stdin, SHA3_ELF, config, proof_mode)
 }
 
-fn bench_fibonacci(config: &BenchmarkConfig) -> BenchmarkResult {
+fn bench_fibonacci(config: &BenchmarkConfig, proof_mode: ProofMode) -> BenchmarkResult {
     let mut stdin = SP1Stdin::new();
     stdin.write(&config.n);
-    prove_and_verify(&mut stdin, FIBONACCI_ELF, config.verifier_iterations)
+    run(&mut stdin, FIBONACCI_ELF, config, proof_mode)
 }
 
-fn bench_ecadd(config: &BenchmarkConfig) -> BenchmarkResult {
+fn bench_ecadd(config: &BenchmarkConfig, proof_mode: ProofMode) -> BenchmarkResult {
     let mut stdin = SP1Stdin::new();
     stdin.write(&config.n);
-    prove_and_verify(&mut stdin, ECADD_ELF, config.verifier_iterations)
+    run(&mut stdin, ECADD_ELF, config, proof_mode)
 }
 
-fn bench_ecadd_precompile(config: &BenchmarkConfig) -> BenchmarkResult {
+fn bench_ecadd_precompile(config: &BenchmarkConfig, proof_mode: ProofMode) -> BenchmarkResult {
     let mut stdin = SP1Stdin::new();
     stdin.write(&config.n);
-    prove_and_verify(&mut stdin, ECADD_PRECOMPILE_ELF, config.verifier_iterations)
+    run(&mut stdin, ECADD_PRECOMPILE_ELF, config, proof_mode)
 }
 
-fn bench_mat_mul(config: &BenchmarkConfig) -> BenchmarkResult {
+fn bench_mat_mul(config: &BenchmarkConfig, proof_mode: ProofMode) -> BenchmarkResult {
     let mut stdin = SP1Stdin::new();
     stdin.write(&config.n);
-    prove_and_verify(&mut stdin, MATMUL_ELF, config.verifier_iterations)
+    run(&mut stdin, MATMUL_ELF, config, proof_mode)
 }
 
-fn bench_blake(config: &BenchmarkConfig) -> BenchmarkResult {
+fn bench_blake(config: &BenchmarkConfig, proof_mode: ProofMode) -> BenchmarkResult {
     let mut stdin = SP1Stdin::new();
     let input = vec![5u8; config.n as usize];
     stdin.write(&input);
-    prove_and_verify(&mut stdin, BLAKE_ELF, config.verifier_iterations)
+    run(&mut stdin, BLAKE_ELF, config, proof_mode)
 }
 
-fn bench_blake_chain(config: &BenchmarkConfig) -> BenchmarkResult {
+fn bench_blake_chain(config: &BenchmarkConfig, proof_mode: ProofMode) -> BenchmarkResult {
     let mut stdin = SP1Stdin::new();
     let input = [5u8; 32];
     stdin.write(&input);
     stdin.write(&config.n);
-    prove_and_verify(&mut stdin, BLAKE_CHAIN_ELF, config.verifier_iterations)
+    run(&mut stdin, BLAKE_CHAIN_ELF, config, proof_mode)
 }
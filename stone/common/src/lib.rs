@@ -5,7 +5,7 @@ use std::process::Command;
 use std::time::Duration;
 use clap::{arg, Parser};
 use std::env;
-use utils::{BenchmarkConfig, BenchmarkResult};
+use utils::{BenchmarkConfig, BenchmarkResult, ExecutionReport, Mode};
 
 /// A tool to build and optionally benchmark a cargo project
 #[derive(Parser, Debug)]
@@ -17,6 +17,8 @@ pub struct Cli {
     pub program: String,
     #[arg(long, default_value = "1")]
     pub verifier_iterations: u32,
+    #[arg(long, value_enum, default_value_t = Mode::Prove)]
+    pub mode: Mode,
 }
 
 pub fn prove_and_verify(command: &str, args: Vec<&str>, output_file: String) -> (usize, Duration, Duration)  {
@@ -103,31 +105,7 @@ pub fn prove_and_verify(command: &str, args: Vec<&str>, output_file: String) ->
 }
 
 pub fn bench(config: BenchmarkConfig, program_path: &str, program_input: &str, parameter_file: &str) -> BenchmarkResult {
-    // Compile Cairo code
-    let program_name = std::path::Path::new(program_path)
-        .file_stem()
-        .unwrap()
-        .to_str()
-        .unwrap();
-    let output_path = format!("programs/{}.json", program_name);
-    let status = Command::new("cairo-compile")
-        .arg(&program_path)
-        .arg("--output")
-        .arg(&output_path)
-        .arg("--proof_mode")
-        .status();
-
-    match status {
-        Ok(status) if status.success() => {
-            println!("Compilation successful! Compiled file saved to: {}", output_path);
-        }
-        Ok(status) => {
-            eprintln!("Compilation failed with exit code: {}", status.code().unwrap_or(-1));
-        }
-        Err(err) => {
-            eprintln!("Failed to run cairo-compile: {}", err);
-        }
-    }
+    let output_path = compile(program_path);
 
     // compute cycle count
     let steps_command = format!("cairo-run --program={} --cairo_layout_params_file=../configs/cairo_layout_params_file.json --cairo_pie_output=get_steps.zip --layout=dynamic --program_input={}", output_path, program_input);
@@ -205,7 +183,88 @@ pub fn bench(config: BenchmarkConfig, program_path: &str, program_input: &str, p
     }
 }
 
+/// Run the program in the Cairo VM without proving it
+pub fn execute(program_path: &str, program_input: &str) -> BenchmarkResult {
+    let output_path = compile(program_path);
+
+    let steps_command = format!("cairo-run --program={} --cairo_layout_params_file=../configs/cairo_layout_params_file.json --cairo_pie_output=get_steps.zip --layout=dynamic --program_input={} --print_output --print_info", output_path, program_input);
+    let start = Instant::now();
+    let (cycle_count, stdout) = run_steps(&steps_command);
+    let duration = start.elapsed();
+
+    BenchmarkResult {
+        cycle_count: cycle_count as usize,
+        execution: Some(ExecutionReport {
+            duration,
+            trace_length: trace_length(&stdout),
+            public_output: program_output(&stdout),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// Felts printed by `cairo-run --print_output`
+fn program_output(stdout: &str) -> String {
+    stdout
+        .lines()
+        .skip_while(|line| !line.starts_with("Program output:"))
+        .skip(1)
+        .map(|line| line.trim())
+        .take_while(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Steps printed by `cairo-run --print_info`, padded to the next
+/// power of two as the proof mode run does before proving
+fn trace_length(stdout: &str) -> Option<u64> {
+    let steps: u64 = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("Number of steps:"))?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()?;
+    Some(steps.next_power_of_two())
+}
+
+fn compile(program_path: &str) -> String {
+    // Compile Cairo code
+    let program_name = std::path::Path::new(program_path)
+        .file_stem()
+        .unwrap()
+        .to_str()
+        .unwrap();
+    let output_path = format!("programs/{}.json", program_name);
+    let status = Command::new("cairo-compile")
+        .arg(&program_path)
+        .arg("--output")
+        .arg(&output_path)
+        .arg("--proof_mode")
+        .status();
+
+    match status {
+        Ok(status) if status.success() => {
+            println!("Compilation successful! Compiled file saved to: {}", output_path);
+        }
+        Ok(status) => {
+            eprintln!("Compilation failed with exit code: {}", status.code().unwrap_or(-1));
+        }
+        Err(err) => {
+            eprintln!("Failed to run cairo-compile: {}", err);
+        }
+    }
+
+    output_path
+}
+
 pub fn compute_cycle_count(steps_command: &str) -> u64 {
+    run_steps(steps_command).0
+}
+
+/// Run `steps_command` and return the number of steps and its output
+fn run_steps(steps_command: &str) -> (u64, String) {
     println!("Computing n_steps ...");
 
     let output = Command::new("sh")
@@ -244,5 +303,5 @@ pub fn compute_cycle_count(steps_command: &str) -> u64 {
         None
     };
 
-    n_steps.unwrap()
+    (n_steps.unwrap(), String::from_utf8_lossy(&output.stdout).into_owned())
 }
\ No newline at end of file
@@ -1,7 +1,7 @@
 use clap::{Parser};
-use common::{bench, Cli};
+use common::{bench, execute, Cli};
 use std::fs;
-use utils::{BenchmarkConfig, BenchmarkResult};
+use utils::{BenchmarkConfig, BenchmarkResult, Mode};
 
 fn main() {
     // read args from cli
@@ -11,6 +11,8 @@ fn main() {
         n: cli.n,
         program: cli.program.clone(),
         verifier_iterations: cli.verifier_iterations,
+        mode: cli.mode,
+        ..Default::default()
     };
 
     let result = run(config);
@@ -22,6 +24,9 @@ fn run(config: BenchmarkConfig) -> BenchmarkResult {
     let input = format!("{{\"iterations\": {}}}", config.n);
     let program_input = "programs/input.json";
     fs::write(program_input, input).expect("Failed to write input file");
+    if config.mode == Mode::Execute {
+        return execute(&program_path, program_input);
+    }
     let parameter_file = match config.n {
         4096 => "../configs/parameter_65536_32.json".to_string(),
         8192 => "../configs/parameter_65536_64.json".to_string(),
@@ -1,9 +1,9 @@
 use clap::Parser;
 use std::fs;
-use utils::{BenchmarkConfig, BenchmarkResult};
+use utils::{BenchmarkConfig, BenchmarkResult, Mode};
 
 pub mod util;
-use util::prove_and_verify;
+use util::{execute, prove_and_verify};
 
 
 /// A tool to build and optionally benchmark a cargo project
@@ -19,6 +19,9 @@ pub struct Cli {
     
     #[arg(long, default_value = "1")]
     pub verifier_iterations: u32,
+
+    #[arg(long, value_enum, default_value_t = Mode::Prove)]
+    pub mode: Mode,
 }
 
 
@@ -29,6 +32,8 @@ fn main() {
         n: cli.n,
         program: cli.program.clone(),
         verifier_iterations: cli.verifier_iterations,
+        mode: cli.mode,
+        ..Default::default()
     };
 
     let result = match cli.program.as_str() {
@@ -61,6 +66,9 @@ fn bench_fibonacci(config: &BenchmarkConfig) -> BenchmarkResult {
     let memory = "./fib/memory.bin".to_string();
 
     let out_dir = "./fib".to_string();
+    if config.mode == Mode::Execute {
+        return execute(program_input, program_path, output_path);
+    }
     prove_and_verify(program_input, program_path, output_path, public_input, private_input, trace, memory, out_dir, config.verifier_iterations)
 }
 
@@ -78,6 +86,9 @@ fn bench_mat_mul(config: &BenchmarkConfig) -> BenchmarkResult {
     let memory = "./mat_mul/memory.bin".to_string();
 
     let out_dir = "./mat_mul".to_string();
+    if config.mode == Mode::Execute {
+        return execute(program_input, program_path, output_path);
+    }
     prove_and_verify(program_input, program_path, output_path, public_input, private_input, trace, memory, out_dir, config.verifier_iterations)
 }
 
@@ -95,6 +106,9 @@ fn bench_sha2(config: &BenchmarkConfig) -> BenchmarkResult {
     let memory = "./sha2/memory.bin".to_string();
 
     let out_dir = "./sha2".to_string();
+    if config.mode == Mode::Execute {
+        return execute(program_input, program_path, output_path);
+    }
     prove_and_verify(program_input, program_path, output_path, public_input, private_input, trace, memory, out_dir, config.verifier_iterations)
 }
 
@@ -112,6 +126,9 @@ fn bench_sha2_chain(config: &BenchmarkConfig) -> BenchmarkResult {
     let memory = "./sha2-chain/memory.bin".to_string();
 
     let out_dir = "./sha2-chain".to_string();
+    if config.mode == Mode::Execute {
+        return execute(program_input, program_path, output_path);
+    }
     prove_and_verify(program_input, program_path, output_path, public_input, private_input, trace, memory, out_dir, config.verifier_iterations)
 }
 
@@ -130,6 +147,9 @@ fn bench_sha3(config: &BenchmarkConfig) -> BenchmarkResult {
     let memory = "./sha3/memory.bin".to_string();
 
     let out_dir = "./sha3".to_string();
+    if config.mode == Mode::Execute {
+        return execute(program_input, program_path, output_path);
+    }
     prove_and_verify(program_input, program_path, output_path, public_input, private_input, trace, memory, out_dir, config.verifier_iterations)
 }
 
@@ -147,6 +167,9 @@ fn bench_sha3_chain(config: &BenchmarkConfig) -> BenchmarkResult {
     let memory = "./sha3-chain/memory.bin".to_string();
 
     let out_dir = "./sha3-chain".to_string();
+    if config.mode == Mode::Execute {
+        return execute(program_input, program_path, output_path);
+    }
     prove_and_verify(program_input, program_path, output_path, public_input, private_input, trace, memory, out_dir, config.verifier_iterations)
 }
 
@@ -164,6 +187,9 @@ fn bench_ec(config: &BenchmarkConfig) -> BenchmarkResult {
     let memory = "./ec/memory.bin".to_string();
 
     let out_dir = "./ec".to_string();
+    if config.mode == Mode::Execute {
+        return execute(program_input, program_path, output_path);
+    }
     prove_and_verify(program_input, program_path, output_path, public_input, private_input, trace, memory, out_dir, config.verifier_iterations)
 }
 
@@ -181,6 +207,9 @@ fn bench_blake_precompile(config: &BenchmarkConfig) -> BenchmarkResult {
     let memory = "./blake-precompile/memory.bin".to_string();
 
     let out_dir = "./blake-precompile".to_string();
+    if config.mode == Mode::Execute {
+        return execute(program_input, program_path, output_path);
+    }
     prove_and_verify(program_input, program_path, output_path, public_input, private_input, trace, memory, out_dir, config.verifier_iterations)
 }
 
@@ -198,5 +227,8 @@ fn bench_blake_chain_precompile(config: &BenchmarkConfig) -> BenchmarkResult {
     let memory = "./blake-chain-precompile/memory.bin".to_string();
 
     let out_dir = "./blake-chain-precompile".to_string();
+    if config.mode == Mode::Execute {
+        return execute(program_input, program_path, output_path);
+    }
     prove_and_verify(program_input, program_path, output_path, public_input, private_input, trace, memory, out_dir, config.verifier_iterations)
 }
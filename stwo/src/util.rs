@@ -10,7 +10,7 @@ use cairo_air::PreProcessedTraceVariant;
 use std::path::Path;
 use std::time::Instant;
 use std::process::Command;
-use utils::{size, BenchmarkResult};
+use utils::{size, BenchmarkResult, ExecutionReport};
 
 use std::collections::HashMap;
 use std::rc::Rc;
@@ -44,24 +44,7 @@ pub fn prove_and_verify(
 ) -> BenchmarkResult {
     
     println!("Generating Prover Input Files...");
-    let status = Command::new("cairo-compile")
-        .arg(&program_path)
-        .arg("--output")
-        .arg(&output_path)
-        .arg("--proof_mode")
-        .status();
-
-    match status {
-        Ok(status) if status.success() => {
-            println!("Compilation successful! Compiled file saved to: {}", output_path);
-        }
-        Ok(status) => {
-            eprintln!("Compilation failed with exit code: {}", status.code().unwrap_or(-1));
-        }
-        Err(err) => {
-            eprintln!("Failed to run cairo-compile: {}", err);
-        }
-    }
+    compile(&program_path, &output_path);
 
     gen_prover_input(&output_path, &program_input, &out_dir);
 
@@ -117,11 +100,64 @@ pub fn prove_and_verify(
     }
 }
 
+/// Run the program in the Cairo VM without proving it
+pub fn execute(program_input: String, program_path: String, output_path: String) -> BenchmarkResult {
+    compile(&program_path, &output_path);
+
+    println!("Running Cairo VM...");
+    let start = Instant::now();
+    let mut runner = run_program(&output_path, &program_input);
+    let duration = start.elapsed();
+
+    let mut output = String::new();
+    runner.vm.write_output(&mut output).unwrap();
+
+    BenchmarkResult {
+        cycle_count: runner.vm.get_current_step(),
+        execution: Some(ExecutionReport {
+            duration,
+            // the trace is padded in proof mode
+            trace_length: runner.relocated_trace.as_ref().map(|trace| trace.len() as u64),
+            public_output: output.split_whitespace().collect::<Vec<_>>().join(" "),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+fn compile(program_path: &str, output_path: &str) {
+    let status = Command::new("cairo-compile")
+        .arg(program_path)
+        .arg("--output")
+        .arg(output_path)
+        .arg("--proof_mode")
+        .status();
+
+    match status {
+        Ok(status) if status.success() => {
+            println!("Compilation successful! Compiled file saved to: {}", output_path);
+        }
+        Ok(status) => {
+            eprintln!("Compilation failed with exit code: {}", status.code().unwrap_or(-1));
+        }
+        Err(err) => {
+            eprintln!("Failed to run cairo-compile: {}", err);
+        }
+    }
+}
+
 pub fn gen_prover_input(
     program_file: &str,
     program_input_file: &str,
     output_dir: &str,
 ) {
+    let runner = run_program(program_file, program_input_file);
+    
+    let output_dir = Path::new(output_dir);
+    write_to_files(&runner, output_dir);
+}
+
+fn run_program(program_file: &str, program_input_file: &str) -> CairoRunner {
     let program = Program::from_file(Path::new(program_file), Some("main")).unwrap();
     let program_input = {
         let program_input_file_str = std::fs::read_to_string(program_input_file).unwrap();
@@ -166,10 +202,7 @@ pub fn gen_prover_input(
         ).unwrap()),
     };
 
-    let runner = cairo_run_program(&program, &cairo_run_config, &mut hint_processor).unwrap();
-    
-    let output_dir = Path::new(output_dir);
-    write_to_files(&runner, output_dir);
+    cairo_run_program(&program, &cairo_run_config, &mut hint_processor).unwrap()
 }
 
 fn write_to_files(
@@ -0,0 +1,348 @@
+use std::collections::BTreeMap;
+
+use crate::runner::format_bytes;
+use crate::{
+    stats, BenchmarkConfigAndResult, BenchmarkId, BenchmarkResultWithMemory, BenchmarkStatus,
+};
+
+/// Metrics compared between two result sets, larger is worse for all of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Metric {
+    ProverTime,
+    VerifierTime,
+    ProofSize,
+    Cycles,
+    PeakMemory,
+}
+
+impl Metric {
+    pub const ALL: [Metric; 5] = [
+        Metric::ProverTime,
+        Metric::VerifierTime,
+        Metric::ProofSize,
+        Metric::Cycles,
+        Metric::PeakMemory,
+    ];
+
+    /// Samples of the metric in a result, empty if it was not measured
+    pub fn samples(&self, result: &BenchmarkResultWithMemory) -> Vec<f64> {
+        let durations =
+            |durations: &[std::time::Duration]| durations.iter().map(|d| d.as_secs_f64()).collect();
+        let nonzero = |value: f64| if value > 0.0 { vec![value] } else { vec![] };
+        match self {
+            Metric::ProverTime => durations(&result.result.prover_durations),
+            Metric::VerifierTime => durations(&result.result.verifier_durations),
+            Metric::ProofSize => nonzero(result.result.proof_size as f64),
+            Metric::Cycles => nonzero(result.result.cycle_count as f64),
+            Metric::PeakMemory => nonzero(result.peak_memory as f64),
+        }
+    }
+
+    /// Human readable value of the metric
+    pub fn format(&self, value: f64) -> String {
+        match self {
+            Metric::ProverTime => format!("{:.2} s", value),
+            Metric::VerifierTime => format!("{:.2} ms", value * 1000.0),
+            Metric::ProofSize | Metric::PeakMemory => format_bytes(value as u64),
+            Metric::Cycles => format!("{}", value as u64),
+        }
+    }
+}
+
+impl std::fmt::Display for Metric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Metric::ProverTime => write!(f, "prover time"),
+            Metric::VerifierTime => write!(f, "verifier time"),
+            Metric::ProofSize => write!(f, "proof size"),
+            Metric::Cycles => write!(f, "cycles"),
+            Metric::PeakMemory => write!(f, "peak memory"),
+        }
+    }
+}
+
+/// When a change counts as a regression
+#[derive(Debug, Clone, Default)]
+pub struct Thresholds {
+    /// Largest tolerated relative increase per metric (0.05 = 5%),
+    /// metrics without a threshold are only reported
+    pub max_increase: BTreeMap<Metric, f64>,
+    /// Changes of sampled metrics are only regressions if the
+    /// Mann-Whitney U test p-value is below this significance level
+    pub alpha: f64,
+    /// Whether a benchmark that fails now counts as a regression
+    pub fail_on_broken: bool,
+}
+
+/// Change of a metric between the two result sets
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub metric: Metric,
+    /// Median before and after
+    pub before: f64,
+    pub after: f64,
+    /// Relative change of the median (0.05 = 5% larger)
+    pub relative: f64,
+    /// Significance of the change, if both sides have enough samples
+    pub p_value: Option<f64>,
+    pub regression: bool,
+}
+
+/// How a benchmark present in both result sets differs
+#[derive(Debug, Clone, PartialEq)]
+pub enum Difference {
+    /// Succeeded both times
+    Changes(Vec<Change>),
+    /// Succeeded before, fails now
+    Broken { regression: bool },
+    /// Failed before, succeeds now
+    Fixed,
+    /// Failed both times
+    Failing,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    pub compared: Vec<(BenchmarkId, Difference)>,
+    /// Benchmarks only present in one of the result sets
+    pub only_before: Vec<BenchmarkId>,
+    pub only_after: Vec<BenchmarkId>,
+}
+
+fn change(
+    metric: Metric,
+    before: &BenchmarkResultWithMemory,
+    after: &BenchmarkResultWithMemory,
+    thresholds: &Thresholds,
+) -> Option<Change> {
+    let (before, after) = (metric.samples(before), metric.samples(after));
+    let median_before = stats::median(&before)?;
+    let median_after = stats::median(&after)?;
+    let relative = median_after / median_before - 1.0;
+    let p_value = stats::mann_whitney_u(&before, &after);
+    let regression = thresholds
+        .max_increase
+        .get(&metric)
+        .is_some_and(|max| relative > *max)
+        && p_value.is_none_or(|p| p < thresholds.alpha);
+    Some(Change {
+        metric,
+        before: median_before,
+        after: median_after,
+        relative,
+        p_value,
+        regression,
+    })
+}
+
+/// Match the results of both sets by benchmark id and compare them
+pub fn compare(
+    before: &[BenchmarkConfigAndResult],
+    after: &[BenchmarkConfigAndResult],
+    thresholds: &Thresholds,
+) -> Report {
+    let before: BTreeMap<String, &BenchmarkConfigAndResult> =
+        before.iter().map(|r| (r.id().to_string(), r)).collect();
+    let mut after: BTreeMap<String, &BenchmarkConfigAndResult> =
+        after.iter().map(|r| (r.id().to_string(), r)).collect();
+
+    let mut report = Report::default();
+    for (id, old) in before {
+        let Some(new) = after.remove(&id) else {
+            report.only_before.push(old.id());
+            continue;
+        };
+        let difference = match (&old.result, &new.result) {
+            (BenchmarkStatus::Success(old), BenchmarkStatus::Success(new)) => Difference::Changes(
+                Metric::ALL
+                    .iter()
+                    .filter_map(|metric| change(*metric, old, new, thresholds))
+                    .collect(),
+            ),
+            (BenchmarkStatus::Success(_), _) => Difference::Broken {
+                regression: thresholds.fail_on_broken,
+            },
+            (_, BenchmarkStatus::Success(_)) => Difference::Fixed,
+            _ => Difference::Failing,
+        };
+        report.compared.push((old.id(), difference));
+    }
+    report.only_after = after.values().map(|r| r.id()).collect();
+    report
+}
+
+impl Report {
+    /// Number of regressions, a benchmark that fails now counts as one
+    /// if `fail_on_broken` was set
+    pub fn regressions(&self) -> usize {
+        self.compared
+            .iter()
+            .map(|(_, difference)| match difference {
+                Difference::Changes(changes) => changes.iter().filter(|c| c.regression).count(),
+                Difference::Broken { regression } => *regression as usize,
+                Difference::Fixed | Difference::Failing => 0,
+            })
+            .sum()
+    }
+
+    pub fn print(&self) {
+        for (id, difference) in &self.compared {
+            match difference {
+                Difference::Changes(changes) => {
+                    println!("{}", id);
+                    for change in changes {
+                        let p_value = change
+                            .p_value
+                            .map(|p| format!(" (p = {:.3})", p))
+                            .unwrap_or_default();
+                        println!(
+                            "  {:<14}: {} -> {} {:+.1}%{}{}",
+                            change.metric.to_string(),
+                            change.metric.format(change.before),
+                            change.metric.format(change.after),
+                            change.relative * 100.0,
+                            p_value,
+                            if change.regression {
+                                "  REGRESSION"
+                            } else {
+                                ""
+                            }
+                        );
+                    }
+                }
+                Difference::Broken { regression } => println!(
+                    "{}\n  fails now{}",
+                    id,
+                    if *regression { "  REGRESSION" } else { "" }
+                ),
+                Difference::Fixed => println!("{}\n  succeeds now", id),
+                Difference::Failing => println!("{}\n  fails in both", id),
+            }
+        }
+        for id in &self.only_before {
+            println!("{}: only in the baseline", id);
+        }
+        for id in &self.only_after {
+            println!("{}: only in the new results", id);
+        }
+        println!(
+            "Compared {} benchmarks, {} regressions",
+            self.compared.len(),
+            self.regressions()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::memory::MemoryResult;
+    use crate::{BenchmarkConfig, BenchmarkResult, VM};
+
+    fn result(n: u32, prover_ms: &[u64], proof_size: usize) -> BenchmarkConfigAndResult {
+        BenchmarkConfigAndResult {
+            vm: VM::SP1,
+            config: BenchmarkConfig {
+                n,
+                program: "fib".to_string(),
+                verifier_iterations: 1,
+                ..Default::default()
+            },
+            result: BenchmarkStatus::Success(BenchmarkResultWithMemory {
+                result: BenchmarkResult {
+                    proof_size,
+                    prover_durations: prover_ms
+                        .iter()
+                        .map(|&ms| Duration::from_millis(ms))
+                        .collect(),
+                    ..Default::default()
+                },
+                peak_memory: 1 << 30,
+            }),
+            hygiene: None,
+            provenance: None,
+            session: None,
+            sampling: None,
+        }
+    }
+
+    #[test]
+    fn test_compare_results() {
+        let thresholds = Thresholds {
+            max_increase: [(Metric::ProverTime, 0.05), (Metric::ProofSize, 0.05)].into(),
+            alpha: 0.05,
+            fail_on_broken: true,
+        };
+        let failure = |n| BenchmarkConfigAndResult {
+            result: BenchmarkStatus::Failure(MemoryResult::Signal(9)),
+            ..result(n, &[], 0)
+        };
+        let before = vec![
+            result(1, &[1000, 1010, 990, 1005, 995], 1000),
+            result(2, &[2000, 2100, 1900, 2050, 1950], 1000),
+            result(3, &[3000], 1000),
+            result(4, &[4000], 1000),
+            result(5, &[5000], 1000),
+        ];
+        let after = vec![
+            // significantly slower
+            result(1, &[1200, 1210, 1190, 1205, 1195], 1000),
+            // slower median, but within the noise
+            result(2, &[2300, 1900, 2000, 2150, 1950], 1000),
+            // larger proof
+            result(3, &[3000], 1100),
+            failure(4),
+            result(6, &[6000], 1000),
+        ];
+
+        let report = compare(&before, &after, &thresholds);
+        let regressions: Vec<(u32, Vec<Metric>)> = report
+            .compared
+            .iter()
+            .map(|(id, difference)| {
+                let metrics = match difference {
+                    Difference::Changes(changes) => changes
+                        .iter()
+                        .filter(|c| c.regression)
+                        .map(|c| c.metric)
+                        .collect(),
+                    _ => vec![],
+                };
+                (id.n, metrics)
+            })
+            .collect();
+        assert_eq!(
+            regressions,
+            vec![
+                (1, vec![Metric::ProverTime]),
+                (2, vec![]),
+                (3, vec![Metric::ProofSize]),
+                (4, vec![]),
+            ]
+        );
+        assert_eq!(
+            report.compared[3].1,
+            Difference::Broken { regression: true }
+        );
+        assert_eq!(report.regressions(), 3);
+
+        // without thresholds, changes and failures are only reported
+        let report = compare(
+            &before,
+            &after,
+            &Thresholds {
+                alpha: 0.05,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            report.compared[3].1,
+            Difference::Broken { regression: false }
+        );
+        assert_eq!(report.regressions(), 0);
+        assert_eq!(report.only_before[0].n, 5);
+        assert_eq!(report.only_after[0].n, 6);
+    }
+}
@@ -0,0 +1,182 @@
+use std::path::{Path, PathBuf};
+
+use crate::VM;
+
+/// How the benchmark driver of a VM is invoked.
+///
+/// This mirrors the `run-bench-<vm>` recipes of the justfile.
+#[derive(Debug, Clone, Copy)]
+pub struct Driver {
+    /// Directory the driver runs in, relative to the repository root.
+    /// `{program}` is replaced by the benchmarked program.
+    pub working_dir: &'static str,
+    /// Driver executable, relative to the working directory
+    pub bin: &'static str,
+    /// Exit codes that are recorded as a (failed) result instead of aborting
+    pub allowed_exit_codes: &'static [i32],
+    /// Whether the driver expects `--program <program>`
+    pub takes_program: bool,
+    /// Programs the driver knows how to benchmark
+    pub programs: &'static [&'static str],
+}
+
+impl Driver {
+    pub fn working_dir(&self, root: &Path, program: &str) -> PathBuf {
+        root.join(self.working_dir.replace("{program}", program))
+    }
+
+    pub fn supports(&self, program: &str) -> bool {
+        self.programs.contains(&program)
+    }
+
+    /// Arguments to pass to the driver after the common ones
+    pub fn program_args(&self, program: &str) -> Vec<String> {
+        if self.takes_program {
+            vec!["--program".to_string(), program.to_string()]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+impl VM {
+    pub fn driver(&self) -> Driver {
+        match self {
+            VM::Jolt => Driver {
+                working_dir: "jolt",
+                bin: "target/release/jolt-benchmarks",
+                // 3 when the run does not fit the guest layout
+                allowed_exit_codes: &[0, 3, 101],
+                takes_program: true,
+                programs: &[
+                    "fib",
+                    "sha2",
+                    "sha2-chain",
+                    "sha3",
+                    "sha3-chain",
+                    "mat-mul",
+                    "ec",
+                    "blake",
+                    "blake-chain",
+                ],
+            },
+            VM::SP1 => Driver {
+                working_dir: "sp1/{program}",
+                bin: "../target/release/sp1-script",
+                allowed_exit_codes: &[0],
+                takes_program: true,
+                programs: &[
+                    "fib",
+                    "sha2",
+                    "sha2-precompile",
+                    "sha2-chain",
+                    "sha2-chain-precompile",
+                    "sha3",
+                    "sha3-precompile",
+                    "sha3-chain",
+                    "sha3-chain-precompile",
+                    "mat-mul",
+                    "ec",
+                    "ec-precompile",
+                    "blake",
+                    "blake-chain",
+                ],
+            },
+            VM::Risc0 => Driver {
+                working_dir: "risc0",
+                bin: "target/release/risc0-benchmarks",
+                allowed_exit_codes: &[0],
+                takes_program: true,
+                programs: &[
+                    "fib",
+                    "sha2",
+                    "sha2-precompile",
+                    "sha2-chain",
+                    "sha2-chain-precompile",
+                    "sha3",
+                    "sha3-precompile",
+                    "sha3-chain",
+                    "sha3-chain-precompile",
+                    "mat-mul",
+                    "ec",
+                    "ec-precompile",
+                    "blake",
+                    "blake-chain",
+                ],
+            },
+            VM::Stone => Driver {
+                working_dir: "stone/{program}",
+                bin: "target/release/stone",
+                allowed_exit_codes: &[0, 101],
+                takes_program: false,
+                programs: &[
+                    "fib",
+                    "sha2",
+                    "sha2-chain",
+                    "sha3",
+                    "sha3-builtin",
+                    "sha3-chain",
+                    "sha3-chain-builtin",
+                    "mat-mul",
+                    "ec",
+                ],
+            },
+            VM::Stwo => Driver {
+                working_dir: "stwo",
+                bin: "target/release/stwo-script",
+                allowed_exit_codes: &[0, 101],
+                takes_program: true,
+                programs: &[
+                    "fib",
+                    "sha2",
+                    "sha2-chain",
+                    "sha3",
+                    "sha3-chain",
+                    "mat-mul",
+                    "ec",
+                    "blake-precompile",
+                    "blake-chain-precompile",
+                ],
+            },
+            VM::OpenVM => Driver {
+                working_dir: "openvm",
+                bin: "target/release/openvm-benchmarks",
+                allowed_exit_codes: &[0],
+                takes_program: true,
+                programs: &[
+                    "fib",
+                    "sha2",
+                    "sha2-precompile",
+                    "sha2-chain",
+                    "sha2-chain-precompile",
+                    "sha3",
+                    "sha3-precompile",
+                    "sha3-chain",
+                    "sha3-chain-precompile",
+                    "mat-mul",
+                    "ec",
+                    "ec-precompile",
+                    "blake",
+                    "blake-chain",
+                ],
+            },
+            VM::Native => Driver {
+                working_dir: "native",
+                bin: "target/release/native-benchmarks",
+                allowed_exit_codes: &[0],
+                takes_program: true,
+                programs: &[
+                    "fib",
+                    "sha2",
+                    "sha2-chain",
+                    "sha3",
+                    "sha3-chain",
+                    "mat-mul",
+                    "ec",
+                    "blake",
+                    "blake-chain",
+                ],
+            },
+        }
+    }
+}
@@ -0,0 +1,412 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use crate::metrics::Derived;
+use crate::{stats, BenchmarkConfigAndResult, BenchmarkStatus};
+
+/// File format of an export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    Csv,
+    Jsonl,
+}
+
+/// One benchmark run flattened into a single row, fields that
+/// were not measured or recorded are left empty
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Row {
+    pub id: String,
+    pub vm: String,
+    pub program: String,
+    pub workload: String,
+    pub variant: Option<String>,
+    pub n: u32,
+    pub mode: String,
+    // `key=value` pairs separated by `;`
+    pub backend_options: String,
+    // as reported by the driver, including defaults, same format
+    pub settings: String,
+    pub verifier_iterations: u32,
+
+    // success, failure or skipped
+    pub status: String,
+    pub failure: Option<String>,
+
+    pub prover_samples: usize,
+    pub prover_time_ms_median: Option<f64>,
+    pub prover_time_ms_min: Option<f64>,
+    pub prover_time_ms_max: Option<f64>,
+    pub verifier_samples: usize,
+    pub verifier_time_ms_median: Option<f64>,
+    pub verifier_time_ms_min: Option<f64>,
+    pub verifier_time_ms_max: Option<f64>,
+    pub proof_size_bytes: Option<usize>,
+    pub cycle_count: Option<usize>,
+    pub peak_memory_bytes: Option<u64>,
+    // peak memory as reported by the prover itself
+    pub prover_peak_memory_bytes: Option<u64>,
+    pub execution_time_ms: Option<f64>,
+    pub trace_length: Option<u64>,
+    pub segments: Option<usize>,
+    pub public_output: Option<String>,
+    pub touched_memory_addresses: Option<u64>,
+    pub gas: Option<u64>,
+    // name=count, separated by semicolons
+    pub syscall_counts: Option<String>,
+    pub opcode_counts: Option<String>,
+
+    pub compile_time_ms: Option<f64>,
+    pub prover_preprocessing_ms: Option<f64>,
+    pub verifier_preprocessing_ms: Option<f64>,
+    pub prover_preprocessing_bytes: Option<usize>,
+    pub verifier_preprocessing_bytes: Option<usize>,
+    pub preprocessing_peak_memory_bytes: Option<u64>,
+    pub preprocessing_reused: Option<bool>,
+
+    pub wrap_time_ms: Option<f64>,
+    pub compressed_proof_size_bytes: Option<usize>,
+
+    pub cycles_per_second: Option<f64>,
+    pub cycles_per_second_per_core: Option<f64>,
+    pub bytes_hashed_per_second: Option<f64>,
+    pub ec_additions_per_second: Option<f64>,
+    pub verifier_ms_per_proof_kb: Option<f64>,
+    pub memory_bytes_per_million_cycles: Option<f64>,
+
+    pub hostname: Option<String>,
+    pub git_commit: Option<String>,
+    pub utils_version: Option<String>,
+    pub driver: Option<String>,
+    pub driver_size_bytes: Option<u64>,
+    pub driver_modified_unix: Option<u64>,
+    // separated by spaces
+    pub driver_args: Option<String>,
+    pub started_at_unix: Option<u64>,
+
+    pub session_seed: Option<u64>,
+    pub session_trials: Option<usize>,
+
+    // runs taken while re-sampling noisy samples, and whether they settled
+    pub sampling_runs: Option<u32>,
+    pub sampling_converged: Option<bool>,
+}
+
+fn join_options<V: std::fmt::Display>(options: &BTreeMap<String, V>) -> String {
+    options
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+fn millis(durations: &[std::time::Duration]) -> Vec<f64> {
+    durations.iter().map(|d| d.as_secs_f64() * 1000.0).collect()
+}
+
+impl Row {
+    pub fn new(result: &BenchmarkConfigAndResult) -> Row {
+        let id = result.id();
+        let (workload, variant) = id.name.workload();
+        let mut row = Row {
+            id: id.to_string(),
+            vm: id.name.vm.to_string(),
+            program: id.name.program.clone(),
+            workload: workload.to_string(),
+            variant: variant.map(String::from),
+            n: id.n,
+            mode: id.mode.to_string(),
+            backend_options: join_options(&id.backend_options),
+            verifier_iterations: result.config.verifier_iterations,
+            ..Default::default()
+        };
+
+        match &result.result {
+            BenchmarkStatus::Success(success) => {
+                let prover = millis(&success.result.prover_durations);
+                let verifier = millis(&success.result.verifier_durations);
+                let execution = success.result.execution.as_ref();
+                row.status = "success".to_string();
+                row.settings = join_options(&success.result.settings);
+                row.prover_samples = prover.len();
+                row.prover_time_ms_median = stats::median(&prover);
+                row.prover_time_ms_min = prover.iter().copied().reduce(f64::min);
+                row.prover_time_ms_max = prover.iter().copied().reduce(f64::max);
+                row.verifier_samples = verifier.len();
+                row.verifier_time_ms_median = stats::median(&verifier);
+                row.verifier_time_ms_min = verifier.iter().copied().reduce(f64::min);
+                row.verifier_time_ms_max = verifier.iter().copied().reduce(f64::max);
+                row.proof_size_bytes = Some(success.result.proof_size);
+                row.cycle_count = success.result.cycles();
+                row.peak_memory_bytes = Some(success.peak_memory);
+                row.prover_peak_memory_bytes = success.result.peak_memory;
+                row.execution_time_ms = execution.map(|e| e.duration.as_secs_f64() * 1000.0);
+                row.trace_length = execution.and_then(|e| e.trace_length);
+                row.segments = execution.and_then(|e| e.segments);
+                row.public_output = execution.map(|e| e.public_output.clone());
+                row.touched_memory_addresses = execution.and_then(|e| e.touched_memory_addresses);
+                row.gas = execution.and_then(|e| e.gas);
+                row.syscall_counts = execution
+                    .filter(|e| !e.syscall_counts.is_empty())
+                    .map(|e| join_options(&e.syscall_counts));
+                row.opcode_counts = execution
+                    .filter(|e| !e.opcode_counts.is_empty())
+                    .map(|e| join_options(&e.opcode_counts));
+
+                if let Some(preprocessing) = &success.result.preprocessing {
+                    let millis = |d: std::time::Duration| Some(d.as_secs_f64() * 1000.0);
+                    row.compile_time_ms = millis(preprocessing.compile_duration);
+                    row.prover_preprocessing_ms = millis(preprocessing.prover_duration);
+                    row.verifier_preprocessing_ms = millis(preprocessing.verifier_duration);
+                    row.prover_preprocessing_bytes = Some(preprocessing.prover_size);
+                    row.verifier_preprocessing_bytes = Some(preprocessing.verifier_size);
+                    row.preprocessing_peak_memory_bytes = preprocessing.peak_memory;
+                    row.preprocessing_reused = Some(preprocessing.reused);
+                }
+                if let Some(wrap) = &success.result.wrap {
+                    row.wrap_time_ms = Some(wrap.duration.as_secs_f64() * 1000.0);
+                    row.compressed_proof_size_bytes = Some(wrap.compressed_proof_size);
+                }
+
+                let derived = Derived::new(result);
+                row.cycles_per_second = derived.cycles_per_second;
+                row.cycles_per_second_per_core = derived.cycles_per_second_per_core;
+                row.bytes_hashed_per_second = derived.bytes_hashed_per_second;
+                row.ec_additions_per_second = derived.ec_additions_per_second;
+                row.verifier_ms_per_proof_kb = derived.verifier_ms_per_proof_kb;
+                row.memory_bytes_per_million_cycles = derived.memory_bytes_per_million_cycles;
+            }
+            BenchmarkStatus::Failure(failure) => {
+                row.status = "failure".to_string();
+                row.failure = Some(failure.to_string());
+            }
+            BenchmarkStatus::Skipped(skipped) => {
+                row.status = "skipped".to_string();
+                row.failure = Some(format!("{} had {}", skipped.failed_run, skipped.failure));
+            }
+        }
+
+        if let Some(provenance) = &result.provenance {
+            row.hostname = provenance.hostname.clone();
+            row.git_commit = provenance.git_commit.clone();
+            row.utils_version = Some(provenance.utils_version.clone());
+            row.driver = Some(provenance.driver.clone());
+            row.driver_size_bytes = provenance.driver_size_bytes;
+            row.driver_modified_unix = provenance.driver_modified_unix;
+            row.driver_args = Some(provenance.driver_args.join(" "));
+            row.started_at_unix = Some(provenance.started_at_unix);
+        }
+        if let Some(session) = &result.session {
+            row.session_seed = Some(session.seed);
+            row.session_trials = Some(session.trials.len());
+        }
+        if let Some(sampling) = &result.sampling {
+            row.sampling_runs = Some(sampling.runs);
+            row.sampling_converged = Some(sampling.converged);
+        }
+        row
+    }
+}
+
+/// Write one row per result to `writer`
+pub fn write(
+    results: &[BenchmarkConfigAndResult],
+    format: Format,
+    writer: impl Write,
+) -> io::Result<()> {
+    let rows = results.iter().map(Row::new);
+    match format {
+        Format::Csv => {
+            let mut csv = csv::Writer::from_writer(writer);
+            for row in rows {
+                csv.serialize(row)?;
+            }
+            csv.flush()
+        }
+        Format::Jsonl => {
+            let mut writer = io::BufWriter::new(writer);
+            for row in rows {
+                serde_json::to_writer(&mut writer, &row)?;
+                writeln!(writer)?;
+            }
+            writer.flush()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::memory::MemoryResult;
+    use crate::{
+        BenchmarkConfig, BenchmarkId, BenchmarkName, BenchmarkResult, BenchmarkResultWithMemory,
+        ExecutionReport, Mode, PreprocessingReport, Sampling, SkippedAfterFailure, WrapReport, VM,
+    };
+
+    fn results() -> Vec<BenchmarkConfigAndResult> {
+        let config = |n| BenchmarkConfig {
+            n,
+            program: "sha2-precompile".to_string(),
+            verifier_iterations: 2,
+            ..Default::default()
+        };
+        vec![
+            BenchmarkConfigAndResult {
+                vm: VM::SP1,
+                config: config(4096),
+                result: BenchmarkStatus::Success(BenchmarkResultWithMemory {
+                    result: BenchmarkResult {
+                        proof_size: 1500,
+                        prover_durations: vec![Duration::from_millis(2000)],
+                        verifier_durations: vec![
+                            Duration::from_millis(30),
+                            Duration::from_millis(10),
+                        ],
+                        cycle_count: 100_000,
+                        settings: [("layout".to_string(), "large".to_string())].into(),
+                        wrap: Some(WrapReport {
+                            duration: Duration::from_millis(500),
+                            compressed_proof_size: 1 << 20,
+                        }),
+                        preprocessing: Some(PreprocessingReport {
+                            prover_duration: Duration::from_millis(1500),
+                            prover_size: 64 << 20,
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    },
+                    peak_memory: 1 << 30,
+                }),
+                hygiene: None,
+                provenance: None,
+                session: None,
+                sampling: Some(Sampling {
+                    runs: 4,
+                    converged: false,
+                }),
+            },
+            BenchmarkConfigAndResult {
+                vm: VM::SP1,
+                config: config(8192),
+                result: BenchmarkStatus::Failure(MemoryResult::Signal(9)),
+                hygiene: None,
+                provenance: None,
+                session: None,
+                sampling: None,
+            },
+            BenchmarkConfigAndResult {
+                vm: VM::SP1,
+                config: config(16384),
+                result: BenchmarkStatus::Skipped(SkippedAfterFailure {
+                    failed_run: BenchmarkId {
+                        name: BenchmarkName::parse("sp1-sha2-precompile").unwrap(),
+                        n: 8192,
+                        backend_options: Default::default(),
+                        mode: Mode::Prove,
+                    },
+                    failure: MemoryResult::Signal(9),
+                }),
+                hygiene: None,
+                provenance: None,
+                session: None,
+                sampling: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_export_rows() {
+        let rows: Vec<Row> = results().iter().map(Row::new).collect();
+
+        assert_eq!(rows[0].workload, "sha2");
+        assert_eq!(rows[0].variant.as_deref(), Some("precompile"));
+        assert_eq!(rows[0].status, "success");
+        assert_eq!(rows[0].prover_time_ms_median, Some(2000.0));
+        assert_eq!(rows[0].verifier_samples, 2);
+        assert_eq!(rows[0].verifier_time_ms_median, Some(20.0));
+        assert_eq!(rows[0].verifier_time_ms_min, Some(10.0));
+        assert_eq!(rows[0].cycle_count, Some(100_000));
+        assert_eq!(rows[0].cycles_per_second, Some(50_000.0));
+        assert_eq!(rows[0].bytes_hashed_per_second, Some(2048.0));
+        assert_eq!(rows[0].settings, "layout=large");
+        assert_eq!(rows[0].prover_preprocessing_ms, Some(1500.0));
+        assert_eq!(rows[0].prover_preprocessing_bytes, Some(64 << 20));
+        assert_eq!(rows[0].preprocessing_reused, Some(false));
+        assert_eq!(rows[1].compile_time_ms, None);
+        assert_eq!(rows[0].wrap_time_ms, Some(500.0));
+        assert_eq!(rows[0].compressed_proof_size_bytes, Some(1 << 20));
+        assert_eq!(rows[1].wrap_time_ms, None);
+        assert_eq!(rows[0].sampling_runs, Some(4));
+        assert_eq!(rows[0].sampling_converged, Some(false));
+
+        assert_eq!(rows[1].status, "failure");
+        assert_eq!(rows[1].failure.as_deref(), Some("out of memory"));
+        assert_eq!(rows[1].cycle_count, None);
+
+        assert_eq!(rows[2].status, "skipped");
+        assert_eq!(
+            rows[2].failure.as_deref(),
+            Some("sp1-sha2-precompile-n8192 had out of memory")
+        );
+
+        // drivers that could not count the cycles leave them at 0
+        let mut uncounted = results().remove(0);
+        if let BenchmarkStatus::Success(success) = &mut uncounted.result {
+            success.result.cycle_count = 0;
+        }
+        let row = Row::new(&uncounted);
+        assert_eq!(row.cycle_count, None);
+        assert_eq!(row.cycles_per_second, None);
+
+        let mut executed = results().remove(0);
+        if let BenchmarkStatus::Success(success) = &mut executed.result {
+            success.result.execution = Some(ExecutionReport {
+                syscall_counts: [
+                    ("SHA_COMPRESS".to_string(), 2),
+                    ("SHA_EXTEND".to_string(), 2),
+                ]
+                .into(),
+                gas: Some(7),
+                ..Default::default()
+            });
+        }
+        let row = Row::new(&executed);
+        assert_eq!(
+            row.syscall_counts.as_deref(),
+            Some("SHA_COMPRESS=2;SHA_EXTEND=2")
+        );
+        assert_eq!(row.opcode_counts, None);
+        assert_eq!(row.gas, Some(7));
+        assert_eq!(rows[0].syscall_counts, None);
+    }
+
+    #[test]
+    fn test_export_formats() {
+        let mut csv = Vec::new();
+        write(&results(), Format::Csv, &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("id,vm,program,workload,variant,n,"));
+        assert!(lines[1].starts_with(
+            "sp1-sha2-precompile-n4096,sp1,sha2-precompile,sha2,precompile,4096,prove,"
+        ));
+        // every row has the same number of columns
+        let columns = lines[0].split(',').count();
+        assert!(lines.iter().all(|line| line.split(',').count() == columns));
+
+        let mut jsonl = Vec::new();
+        write(&results(), Format::Jsonl, &mut jsonl).unwrap();
+        let rows: Vec<serde_json::Value> = String::from_utf8(jsonl)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0]["proof_size_bytes"], 1500);
+        assert_eq!(rows[1]["proof_size_bytes"], serde_json::Value::Null);
+    }
+}
@@ -0,0 +1,368 @@
+use std::collections::BTreeMap;
+
+use crate::compare::Metric;
+use crate::matrix::family;
+use crate::{stats, BenchmarkConfigAndResult, BenchmarkId, BenchmarkStatus, Mode};
+
+/// Metrics whose scaling in n is fitted
+pub const METRICS: [Metric; 3] = [Metric::ProverTime, Metric::PeakMemory, Metric::ProofSize];
+
+/// Fewer points than this are not fitted
+const MIN_POINTS: usize = 3;
+
+/// How a metric grows with n
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Model {
+    /// a + b·n
+    Linear,
+    /// a + b·n·log₂ n
+    NLogN,
+    /// a·n^b
+    PowerLaw,
+}
+
+impl std::fmt::Display for Model {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Model::Linear => write!(f, "linear"),
+            Model::NLogN => write!(f, "n·log n"),
+            Model::PowerLaw => write!(f, "power law"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fit {
+    pub model: Model,
+    pub a: f64,
+    pub b: f64,
+    /// Coefficient of determination of the fit, computed on the measured values
+    pub r_squared: f64,
+}
+
+impl Fit {
+    pub fn predict(&self, n: f64) -> f64 {
+        match self.model {
+            Model::Linear => self.a + self.b * n,
+            Model::NLogN => self.a + self.b * n * n.log2(),
+            Model::PowerLaw => self.a * n.powf(self.b),
+        }
+    }
+}
+
+// least squares fit of y = a + b·x
+fn least_squares(points: &[(f64, f64)]) -> Option<(f64, f64)> {
+    let count = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / count;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / count;
+    let covariance: f64 = points
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum();
+    let variance: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    if variance == 0.0 {
+        return None;
+    }
+    let b = covariance / variance;
+    Some((mean_y - b * mean_x, b))
+}
+
+fn fit_model(model: Model, points: &[(f64, f64)]) -> Option<Fit> {
+    let (a, b) = match model {
+        Model::Linear => least_squares(points)?,
+        Model::NLogN => {
+            let transformed: Vec<_> = points.iter().map(|&(n, y)| (n * n.log2(), y)).collect();
+            least_squares(&transformed)?
+        }
+        Model::PowerLaw => {
+            if points.iter().any(|&(n, y)| n <= 0.0 || y <= 0.0) {
+                return None;
+            }
+            let transformed: Vec<_> = points.iter().map(|&(n, y)| (n.ln(), y.ln())).collect();
+            let (ln_a, b) = least_squares(&transformed)?;
+            (ln_a.exp(), b)
+        }
+    };
+    let mut fit = Fit {
+        model,
+        a,
+        b,
+        r_squared: 0.0,
+    };
+
+    let mean = points.iter().map(|(_, y)| y).sum::<f64>() / points.len() as f64;
+    let total: f64 = points.iter().map(|(_, y)| (y - mean).powi(2)).sum();
+    let residual: f64 = points
+        .iter()
+        .map(|&(n, y)| (y - fit.predict(n)).powi(2))
+        .sum();
+    fit.r_squared = if total > 0.0 {
+        1.0 - residual / total
+    } else if residual <= f64::EPSILON * mean.abs() {
+        1.0
+    } else {
+        0.0
+    };
+    Some(fit)
+}
+
+/// The model fitting the (n, value) points best, preferring
+/// the simpler model if several fit equally well
+pub fn best_fit(points: &[(f64, f64)]) -> Option<Fit> {
+    if points.len() < MIN_POINTS {
+        return None;
+    }
+    [Model::Linear, Model::NLogN, Model::PowerLaw]
+        .into_iter()
+        .filter_map(|model| fit_model(model, points))
+        .fold(None, |best: Option<Fit>, fit| match best {
+            Some(best) if best.r_squared >= fit.r_squared => Some(best),
+            _ => Some(fit),
+        })
+}
+
+/// A measured point far off the fitted curve
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Deviation {
+    pub n: u32,
+    pub measured: f64,
+    pub predicted: f64,
+    /// Relative deviation from the prediction (0.25 = 25% above)
+    pub relative: f64,
+}
+
+/// Points that deviate from the fit of the remaining points by more than `threshold`.
+///
+/// Outliers are removed one at a time, always the one whose removal fits the
+/// remaining points best, so a single outlier does not drag the curve away
+/// from its neighbours and get them flagged as well.
+fn outliers(model: Model, mut points: Vec<(f64, f64)>, threshold: f64) -> Vec<Deviation> {
+    let mut deviations = Vec::new();
+    while points.len() > MIN_POINTS {
+        let candidate = (0..points.len())
+            .filter_map(|index| {
+                let mut others = points.clone();
+                others.remove(index);
+                Some((index, fit_model(model, &others)?))
+            })
+            .max_by(|(_, a), (_, b)| a.r_squared.total_cmp(&b.r_squared));
+        let Some((index, fit)) = candidate else {
+            break;
+        };
+        let (n, measured) = points[index];
+        let predicted = fit.predict(n);
+        let relative = measured / predicted - 1.0;
+        if predicted <= 0.0 || relative.abs() <= threshold {
+            break;
+        }
+        deviations.push(Deviation {
+            n: n as u32,
+            measured,
+            predicted,
+            relative,
+        });
+        points.remove(index);
+    }
+    deviations.sort_by_key(|deviation| deviation.n);
+    deviations
+}
+
+/// Scaling of one metric of a benchmark over all measured n
+#[derive(Debug, Clone, PartialEq)]
+pub struct Analysis {
+    /// The benchmark, with n set to 0
+    pub family: BenchmarkId,
+    pub metric: Metric,
+    pub fit: Fit,
+    /// Predicted value at the requested n
+    pub extrapolated: Option<(u32, f64)>,
+    pub deviations: Vec<Deviation>,
+}
+
+/// Fit every metric of every benchmark against n.
+///
+/// Points deviating by more than `threshold` (0.25 = 25%) from the
+/// fit of the other points are reported, they are usually padding
+/// cliffs of power-of-two traces or measurement glitches.
+pub fn analyze(
+    results: &[BenchmarkConfigAndResult],
+    at: Option<u32>,
+    threshold: f64,
+) -> Vec<Analysis> {
+    // family -> n -> result
+    let mut families: BTreeMap<String, (BenchmarkId, BTreeMap<u32, _>)> = BTreeMap::new();
+    for result in results {
+        let BenchmarkStatus::Success(success) = &result.result else {
+            continue;
+        };
+        let id = result.id();
+        let family = family(&id);
+        families
+            .entry(family.to_string())
+            .or_insert_with(|| (family, BTreeMap::new()))
+            .1
+            .insert(id.n, success);
+    }
+
+    let mut analyses = Vec::new();
+    for (family, runs) in families.into_values() {
+        for metric in METRICS {
+            let points: Vec<(f64, f64)> = runs
+                .iter()
+                .filter(|(n, _)| **n > 0)
+                .filter_map(|(n, success)| {
+                    Some((*n as f64, stats::median(&metric.samples(success))?))
+                })
+                .collect();
+            let Some(fit) = best_fit(&points) else {
+                continue;
+            };
+            let deviations = outliers(fit.model, points, threshold);
+            analyses.push(Analysis {
+                family: family.clone(),
+                metric,
+                fit,
+                extrapolated: at.map(|n| (n, fit.predict(n as f64))),
+                deviations,
+            });
+        }
+    }
+    analyses
+}
+
+/// Name of a benchmark family, e.g. `sp1-sha2 (proof-mode=core)`
+fn name(family: &BenchmarkId) -> String {
+    let mut name = family.name.to_string();
+    let options: Vec<String> = family
+        .backend_options
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect();
+    if !options.is_empty() {
+        name += &format!(" ({})", options.join(", "));
+    }
+    if family.mode != Mode::Prove {
+        name += &format!(" [{}]", family.mode);
+    }
+    name
+}
+
+pub fn print(analyses: &[Analysis]) {
+    let mut current = None;
+    for analysis in analyses {
+        if current != Some(&analysis.family) {
+            println!("{}", name(&analysis.family));
+            current = Some(&analysis.family);
+        }
+        let extrapolated = analysis
+            .extrapolated
+            .map(|(n, value)| format!(", {} at n = {}", analysis.metric.format(value), n))
+            .unwrap_or_default();
+        println!(
+            "  {:<14}: {}, R² = {:.4}{}",
+            analysis.metric.to_string(),
+            analysis.fit.model,
+            analysis.fit.r_squared,
+            extrapolated
+        );
+        for deviation in &analysis.deviations {
+            println!(
+                "    n = {} deviates {:+.0}% from the fit ({} instead of {})",
+                deviation.n,
+                deviation.relative * 100.0,
+                analysis.metric.format(deviation.measured),
+                analysis.metric.format(deviation.predicted)
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{BenchmarkConfig, BenchmarkResult, BenchmarkResultWithMemory, VM};
+
+    fn points(f: impl Fn(f64) -> f64) -> Vec<(f64, f64)> {
+        (10..16)
+            .map(|exp| {
+                let n = (1u64 << exp) as f64;
+                (n, f(n))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_best_fit() {
+        let linear = best_fit(&points(|n| 5.0 + 2.0 * n)).unwrap();
+        assert_eq!(linear.model, Model::Linear);
+        assert!((linear.r_squared - 1.0).abs() < 1e-9);
+        assert!((linear.predict(1e6) - 2_000_005.0).abs() < 1e-3);
+
+        let n_log_n = best_fit(&points(|n| 3.0 * n * n.log2())).unwrap();
+        assert_eq!(n_log_n.model, Model::NLogN);
+
+        let power_law = best_fit(&points(|n| 0.5 * n.powf(1.5))).unwrap();
+        assert_eq!(power_law.model, Model::PowerLaw);
+        assert!((power_law.b - 1.5).abs() < 1e-9);
+
+        // constant values are fitted perfectly by the simplest model
+        let constant = best_fit(&points(|_| 260.0)).unwrap();
+        assert_eq!(constant.model, Model::Linear);
+        assert_eq!(constant.r_squared, 1.0);
+
+        assert_eq!(best_fit(&points(|n| n)[..2]), None);
+    }
+
+    #[test]
+    fn test_analyze_flags_deviations() {
+        // prover time doubles with n, except for a cliff at n = 4096
+        let results: Vec<BenchmarkConfigAndResult> = [1024, 2048, 4096, 8192, 16384, 32768]
+            .into_iter()
+            .map(|n| {
+                let secs = n as u64 / 256 * if n == 4096 { 2 } else { 1 };
+                BenchmarkConfigAndResult {
+                    vm: VM::Jolt,
+                    config: BenchmarkConfig {
+                        n,
+                        program: "sha2".to_string(),
+                        verifier_iterations: 1,
+                        ..Default::default()
+                    },
+                    result: BenchmarkStatus::Success(BenchmarkResultWithMemory {
+                        result: BenchmarkResult {
+                            prover_durations: vec![Duration::from_secs(secs)],
+                            ..Default::default()
+                        },
+                        peak_memory: 1 << 30,
+                    }),
+                    hygiene: None,
+                    provenance: None,
+                    session: None,
+                    sampling: None,
+                }
+            })
+            .collect();
+
+        let analyses = analyze(&results, Some(65536), 0.25);
+        let prover = analyses
+            .iter()
+            .find(|analysis| analysis.metric == Metric::ProverTime)
+            .unwrap();
+        assert_eq!(prover.family.to_string(), "jolt-sha2-n0");
+        assert_eq!(
+            prover.deviations.iter().map(|d| d.n).collect::<Vec<_>>(),
+            vec![4096]
+        );
+        let (n, predicted) = prover.extrapolated.unwrap();
+        assert_eq!(n, 65536);
+        assert!((predicted / 256.0 - 1.0).abs() < 0.1, "{}", predicted);
+
+        let memory = analyses
+            .iter()
+            .find(|analysis| analysis.metric == Metric::PeakMemory)
+            .unwrap();
+        assert!(memory.deviations.is_empty());
+    }
+}
@@ -0,0 +1,281 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+// the kernel always reports /proc times in USER_HZ, which is 100 on Linux
+const CLOCK_TICKS_PER_SEC: f64 = 100.0;
+
+// window over which per-process cpu usage is sampled
+const PROCESS_SAMPLE_WINDOW: Duration = Duration::from_millis(500);
+
+// a process using more than this fraction of a core is considered competing
+const HEAVY_PROCESS_CPU_THRESHOLD: f64 = 0.5;
+
+// thresholds above which the machine is considered noisy
+const MAX_LOAD_AVERAGE: f64 = 1.0;
+const MIN_AVAILABLE_MEMORY_RATIO: f64 = 0.8;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HeavyProcess {
+    pub pid: u32,
+    pub name: String,
+    // fraction of a single core used during the sampling window
+    pub cpu_usage: f64,
+}
+
+/// State of the machine right before a benchmark is launched.
+///
+/// Every field is optional, since not every platform exposes all of them
+/// (e.g. macOS has neither `/proc` nor `/sys`).
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct HygieneReport {
+    pub cpu_count: Option<usize>,
+    // distinct scaling governors across all cores
+    pub cpu_governors: Vec<String>,
+    pub turbo_enabled: Option<bool>,
+    pub load_average: Option<[f64; 3]>,
+    pub mem_total_bytes: Option<u64>,
+    pub mem_available_bytes: Option<u64>,
+    pub swap_enabled: Option<bool>,
+    pub transparent_hugepage: Option<String>,
+    pub heavy_processes: Vec<HeavyProcess>,
+    // human readable reasons why the machine is considered noisy
+    pub warnings: Vec<String>,
+}
+
+impl HygieneReport {
+    pub fn is_clean(&self) -> bool {
+        self.warnings.is_empty()
+    }
+}
+
+/// Inspect the machine for sources of noise that commonly
+/// skew benchmark results
+pub fn check() -> HygieneReport {
+    let mut report = HygieneReport {
+        cpu_count: std::thread::available_parallelism().ok().map(|n| n.get()),
+        cpu_governors: cpu_governors(),
+        turbo_enabled: turbo_enabled(),
+        load_average: load_average(),
+        transparent_hugepage: transparent_hugepage(),
+        heavy_processes: heavy_processes(),
+        ..Default::default()
+    };
+
+    if let Some(meminfo) = meminfo() {
+        report.mem_total_bytes = meminfo.get("MemTotal").copied();
+        report.mem_available_bytes = meminfo.get("MemAvailable").copied();
+        report.swap_enabled = meminfo.get("SwapTotal").map(|&total| total > 0);
+    }
+
+    report.warnings = warnings(&report);
+    report
+}
+
+fn warnings(report: &HygieneReport) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    for governor in &report.cpu_governors {
+        if governor != "performance" {
+            warnings.push(format!("cpu frequency governor is '{}'", governor));
+        }
+    }
+
+    if report.turbo_enabled == Some(true) {
+        warnings.push("turbo/boost is enabled".to_string());
+    }
+
+    if let Some([one_minute, _, _]) = report.load_average {
+        if one_minute > MAX_LOAD_AVERAGE {
+            warnings.push(format!("load average is {:.2}", one_minute));
+        }
+    }
+
+    if let (Some(total), Some(available)) = (report.mem_total_bytes, report.mem_available_bytes) {
+        if (available as f64) < total as f64 * MIN_AVAILABLE_MEMORY_RATIO {
+            warnings.push(format!(
+                "only {:.0}% of memory is available",
+                available as f64 / total as f64 * 100.0
+            ));
+        }
+    }
+
+    if report.swap_enabled == Some(true) {
+        warnings.push("swap is enabled".to_string());
+    }
+
+    for process in &report.heavy_processes {
+        warnings.push(format!(
+            "process {} ({}) is using {:.0}% of a core",
+            process.name,
+            process.pid,
+            process.cpu_usage * 100.0
+        ));
+    }
+
+    warnings
+}
+
+fn cpu_governors() -> Vec<String> {
+    let mut governors = BTreeSet::new();
+    let Ok(entries) = fs::read_dir("/sys/devices/system/cpu") else {
+        return Vec::new();
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if !name.starts_with("cpu") || !name[3..].chars().all(|c| c.is_ascii_digit()) {
+            continue;
+        }
+        if let Ok(governor) = fs::read_to_string(entry.path().join("cpufreq/scaling_governor")) {
+            governors.insert(governor.trim().to_string());
+        }
+    }
+    governors.into_iter().collect()
+}
+
+fn turbo_enabled() -> Option<bool> {
+    // intel_pstate exposes the inverse flag
+    if let Ok(no_turbo) = fs::read_to_string("/sys/devices/system/cpu/intel_pstate/no_turbo") {
+        return Some(no_turbo.trim() == "0");
+    }
+    // acpi-cpufreq and amd-pstate
+    if let Ok(boost) = fs::read_to_string("/sys/devices/system/cpu/cpufreq/boost") {
+        return Some(boost.trim() == "1");
+    }
+    None
+}
+
+fn load_average() -> Option<[f64; 3]> {
+    let content = fs::read_to_string("/proc/loadavg").ok()?;
+    let mut fields = content.split_whitespace().map(|f| f.parse::<f64>().ok());
+    Some([fields.next()??, fields.next()??, fields.next()??])
+}
+
+/// Parse /proc/meminfo into a map from key to bytes
+fn meminfo() -> Option<HashMap<String, u64>> {
+    let content = fs::read_to_string("/proc/meminfo").ok()?;
+    let mut info = HashMap::new();
+    for line in content.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let mut parts = value.split_whitespace();
+        let Some(Ok(amount)) = parts.next().map(|v| v.parse::<u64>()) else {
+            continue;
+        };
+        let bytes = match parts.next() {
+            Some("kB") => amount * 1024,
+            _ => amount,
+        };
+        info.insert(key.to_string(), bytes);
+    }
+    Some(info)
+}
+
+fn transparent_hugepage() -> Option<String> {
+    let content = fs::read_to_string("/sys/kernel/mm/transparent_hugepage/enabled").ok()?;
+    // the active mode is shown in brackets, e.g. "always [madvise] never"
+    let start = content.find('[')?;
+    let end = content[start..].find(']')? + start;
+    Some(content[start + 1..end].to_string())
+}
+
+/// Total cpu time (in ticks) consumed by every process, keyed by pid
+fn process_cpu_ticks() -> HashMap<u32, (String, u64)> {
+    let mut ticks = HashMap::new();
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return ticks;
+    };
+    for entry in entries.flatten() {
+        let Ok(pid) = entry.file_name().to_string_lossy().parse::<u32>() else {
+            continue;
+        };
+        if let Some(stat) = read_process_stat(&entry.path().join("stat")) {
+            ticks.insert(pid, stat);
+        }
+    }
+    ticks
+}
+
+fn read_process_stat(path: &Path) -> Option<(String, u64)> {
+    let content = fs::read_to_string(path).ok()?;
+    // the command name is enclosed in parentheses and may itself contain spaces
+    let name_start = content.find('(')?;
+    let name_end = content.rfind(')')?;
+    let name = content[name_start + 1..name_end].to_string();
+    // utime and stime are the 14th and 15th fields, counting from the pid
+    let fields: Vec<&str> = content[name_end + 1..].split_whitespace().collect();
+    let utime = fields.get(11)?.parse::<u64>().ok()?;
+    let stime = fields.get(12)?.parse::<u64>().ok()?;
+    Some((name, utime + stime))
+}
+
+fn heavy_processes() -> Vec<HeavyProcess> {
+    let own_pid = std::process::id();
+    let before = process_cpu_ticks();
+    if before.is_empty() {
+        return Vec::new();
+    }
+    std::thread::sleep(PROCESS_SAMPLE_WINDOW);
+    let after = process_cpu_ticks();
+
+    let mut heavy: Vec<HeavyProcess> = after
+        .into_iter()
+        .filter(|(pid, _)| *pid != own_pid)
+        .filter_map(|(pid, (name, ticks))| {
+            let (_, ticks_before) = before.get(&pid)?;
+            let seconds = ticks.saturating_sub(*ticks_before) as f64 / CLOCK_TICKS_PER_SEC;
+            let cpu_usage = seconds / PROCESS_SAMPLE_WINDOW.as_secs_f64();
+            (cpu_usage > HEAVY_PROCESS_CPU_THRESHOLD).then_some(HeavyProcess {
+                pid,
+                name,
+                cpu_usage,
+            })
+        })
+        .collect();
+    heavy.sort_by(|a, b| b.cpu_usage.total_cmp(&a.cpu_usage));
+    heavy
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quiet_machine_has_no_warnings() {
+        let report = HygieneReport {
+            cpu_count: Some(16),
+            cpu_governors: vec!["performance".to_string()],
+            turbo_enabled: Some(false),
+            load_average: Some([0.05, 0.1, 0.2]),
+            mem_total_bytes: Some(64 << 30),
+            mem_available_bytes: Some(62 << 30),
+            swap_enabled: Some(false),
+            transparent_hugepage: Some("madvise".to_string()),
+            ..Default::default()
+        };
+        assert!(warnings(&report).is_empty());
+    }
+
+    #[test]
+    fn test_noisy_machine_is_reported() {
+        let report = HygieneReport {
+            cpu_governors: vec!["performance".to_string(), "powersave".to_string()],
+            load_average: Some([3.5, 2.0, 1.0]),
+            heavy_processes: vec![HeavyProcess {
+                pid: 42,
+                name: "rustc".to_string(),
+                cpu_usage: 0.98,
+            }],
+            ..Default::default()
+        };
+        let warnings = warnings(&report);
+        assert_eq!(warnings.len(), 3);
+        assert!(warnings[0].contains("powersave"));
+        assert!(warnings[2].contains("rustc"));
+    }
+}
@@ -1,12 +1,31 @@
+use std::collections::BTreeMap;
 use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 
+use crate::hygiene::HygieneReport;
 use crate::memory::MemoryResult;
-
+use crate::provenance::Provenance;
+
+pub mod compare;
+pub mod driver;
+pub mod export;
+pub mod fit;
+pub mod hygiene;
+pub mod matrix;
 pub mod memory;
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
+pub mod metrics;
+pub mod pricing;
+pub mod provenance;
+pub mod results;
+pub mod runner;
+pub mod sampling;
+pub mod search;
+pub mod session;
+pub mod stats;
+pub mod summary;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[serde(rename_all = "lowercase")]
 pub enum VM {
     Jolt,
@@ -15,6 +34,8 @@ pub enum VM {
     Stone,
     Stwo,
     OpenVM,
+    // the workloads as plain Rust on the host, as a baseline
+    Native,
 }
 
 impl std::fmt::Display for VM {
@@ -26,15 +47,60 @@ impl std::fmt::Display for VM {
             VM::Stone => write!(f, "stone"),
             VM::Stwo => write!(f, "stwo"),
             VM::OpenVM => write!(f, "openvm"),
+            VM::Native => write!(f, "native"),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// What a driver does with the benchmarked program
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    /// Prove and verify the program
+    #[default]
+    Prove,
+    /// Only execute the program, reporting cycles but no proof
+    Execute,
+}
+
+impl Mode {
+    pub fn is_prove(&self) -> bool {
+        *self == Mode::Prove
+    }
+}
+
+impl std::fmt::Display for Mode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Mode::Prove => write!(f, "prove"),
+            Mode::Execute => write!(f, "execute"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
 pub struct BenchmarkConfig {
     pub n: u32,
     pub program: String,
     pub verifier_iterations: u32,
+    // driver specific options, passed to the driver as `--<key> <value>`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub backend_options: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Mode::is_prove")]
+    pub mode: Mode,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
@@ -56,6 +122,104 @@ pub struct BenchmarkResult {
     pub cycle_count: usize,
     #[serde(rename = "peak_memory_bytes", skip_serializing_if = "Option::is_none")]
     pub peak_memory: Option<u64>,
+    // reported in execute mode, and by some drivers (SP1) when proving
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub execution: Option<ExecutionReport>,
+    // only reported by drivers with a separate setup stage (Jolt)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preprocessing: Option<PreprocessingReport>,
+    // only reported for proofs wrapped into a SNARK (SP1 Groth16 and PLONK, RISC Zero Groth16)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wrap: Option<WrapReport>,
+    // settings the driver ran with, including defaults of backend options not passed
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub settings: BTreeMap<String, String>,
+}
+
+/// What executing a program without proving it reports
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct ExecutionReport {
+    // fractional milliseconds, native runs of small inputs take microseconds
+    #[serde(
+        serialize_with = "serialize_duration_as_millis",
+        deserialize_with = "deserialize_duration_from_millis",
+        rename = "execution_duration_ms"
+    )]
+    pub duration: Duration,
+    // rows of the trace the prover would commit to, after padding
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_length: Option<u64>,
+    // segments (RISC Zero, OpenVM) or shards (SP1) the execution is split into
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub segments: Option<usize>,
+    // hex encoded public values for RISC-V VMs, the output felts for Cairo VMs
+    pub public_output: String,
+    // executed instructions per opcode, by the opcode names of the VM
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub opcode_counts: BTreeMap<String, u64>,
+    // invocations per syscall, precompiles included
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub syscall_counts: BTreeMap<String, u64>,
+    // distinct memory addresses read or written
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub touched_memory_addresses: Option<u64>,
+    // gas the VM charges for the execution (SP1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas: Option<u64>,
+}
+
+/// The setup before the first proof: building the guest
+/// and preprocessing it for the prover and the verifier
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct PreprocessingReport {
+    #[serde(
+        serialize_with = "serialize_duration_as_millis",
+        deserialize_with = "deserialize_duration_from_millis",
+        rename = "compile_duration_ms"
+    )]
+    pub compile_duration: Duration,
+    #[serde(
+        serialize_with = "serialize_duration_as_millis",
+        deserialize_with = "deserialize_duration_from_millis",
+        rename = "prover_duration_ms"
+    )]
+    pub prover_duration: Duration,
+    #[serde(
+        serialize_with = "serialize_duration_as_millis",
+        deserialize_with = "deserialize_duration_from_millis",
+        rename = "verifier_duration_ms"
+    )]
+    pub verifier_duration: Duration,
+    // serialized size of the prover and verifier preprocessing
+    #[serde(rename = "prover_size_bytes")]
+    pub prover_size: usize,
+    #[serde(rename = "verifier_size_bytes")]
+    pub verifier_size: usize,
+    // peak resident memory of the driver once preprocessed, the guest is built in a subprocess
+    #[serde(
+        rename = "peak_memory_bytes",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub peak_memory: Option<u64>,
+    // loaded from an earlier run instead of computed, the durations are load times
+    #[serde(default)]
+    pub reused: bool,
+}
+
+/// Wrapping the compressed STARK proof into a SNARK that is cheap to verify on-chain
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct WrapReport {
+    // prover time on top of the compressed proof, included in the prover durations
+    #[serde(
+        serialize_with = "serialize_duration_as_millis",
+        deserialize_with = "deserialize_duration_from_millis",
+        rename = "duration_ms"
+    )]
+    pub duration: Duration,
+    // size of the compressed proof that was wrapped, the proof size is the wrapped one
+    #[serde(rename = "compressed_proof_size_bytes")]
+    pub compressed_proof_size: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
@@ -66,17 +230,34 @@ pub struct BenchmarkResultWithMemory {
     pub peak_memory: u64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl BenchmarkResultWithMemory {
+    /// Add the prover and verifier samples of another run of the same benchmark
+    pub fn add_samples(&mut self, other: &BenchmarkResultWithMemory) {
+        self.result
+            .prover_durations
+            .extend(&other.result.prover_durations);
+        self.result
+            .verifier_durations
+            .extend(&other.result.verifier_durations);
+        self.peak_memory = self.peak_memory.max(other.peak_memory);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct BenchmarkName {
     pub vm: VM,
     pub program: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct BenchmarkId {
     #[serde(flatten)]
     pub name: BenchmarkName,
     pub n: u32,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub backend_options: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Mode::is_prove")]
+    pub mode: Mode,
 }
 
 impl std::fmt::Display for BenchmarkName {
@@ -87,15 +268,40 @@ impl std::fmt::Display for BenchmarkName {
 
 impl std::fmt::Display for BenchmarkId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}-n{}", self.name, self.n)
+        write!(f, "{}", self.name)?;
+        // backend options go before n, so that globs like `sp1-fib-n*`
+        // keep matching only the runs with default options
+        if !self.backend_options.is_empty() {
+            let options: Vec<String> = self
+                .backend_options
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value))
+                .collect();
+            write!(f, "-{}", options.join(","))?;
+        }
+        if !self.mode.is_prove() {
+            write!(f, "-{}", self.mode)?;
+        }
+        write!(f, "-n{}", self.n)
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
+// successes outnumber failures, boxing them would not save memory
+#[allow(clippy::large_enum_variant)]
 pub enum BenchmarkStatus {
     Success(BenchmarkResultWithMemory),
     Failure(MemoryResult),
+    // not run, since a smaller n of the same benchmark already exceeded the limits
+    Skipped(SkippedAfterFailure),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedAfterFailure {
+    // the run that exceeded the limits
+    pub failed_run: BenchmarkId,
+    pub failure: MemoryResult,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -106,16 +312,68 @@ pub struct BenchmarkConfigAndResult {
     pub config: BenchmarkConfig,
     // output of the benchmark run
     pub result: BenchmarkStatus,
+    // state of the machine when the benchmark was launched
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hygiene: Option<HygieneReport>,
+    // where and with what the result was produced
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+    // trials of a session, the result merges the samples of all of them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<Session>,
+    // driver runs taken to reach the confidence target of noisy samples
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sampling: Option<Sampling>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Sampling {
+    // times the driver ran, the result merges the samples of all runs
+    pub runs: u32,
+    // false if the samples were still too noisy after the last run
+    pub converged: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    // seed of the shuffled run order
+    pub seed: u64,
+    pub trials: Vec<Trial>,
+}
+
+/// One of the repeated runs of a benchmark in a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trial {
+    // position of the run in the session order
+    pub position: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at_unix: Option<u64>,
+    pub result: BenchmarkStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hygiene: Option<HygieneReport>,
 }
 
 impl BenchmarkConfigAndResult {
     pub fn to_json(&self) -> String {
         serde_json::to_string_pretty(self).unwrap()
     }
+
+    /// Identifier of the benchmark this is the result of
+    pub fn id(&self) -> BenchmarkId {
+        BenchmarkId {
+            name: BenchmarkName {
+                vm: self.vm,
+                program: self.config.program.clone(),
+            },
+            n: self.config.n,
+            backend_options: self.config.backend_options.clone(),
+            mode: self.config.mode,
+        }
+    }
 }
 
 fn serialize_durations_as_millis<S>(
-    durations: &Vec<Duration>,
+    durations: &[Duration],
     serializer: S,
 ) -> Result<S::Ok, S::Error>
 where
@@ -133,10 +391,31 @@ where
     Ok(millis.into_iter().map(Duration::from_millis).collect())
 }
 
+fn serialize_duration_as_millis<S>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    (duration.as_secs_f64() * 1000.0).serialize(serializer)
+}
+
+fn deserialize_duration_from_millis<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(Duration::from_secs_f64(
+        f64::deserialize(deserializer)? / 1000.0,
+    ))
+}
+
 impl BenchmarkResult {
     pub fn to_json(&self) -> String {
         serde_json::to_string_pretty(self).unwrap()
     }
+
+    /// The cycle count, `None` if the driver could not count the cycles and left it at 0
+    pub fn cycles(&self) -> Option<usize> {
+        Some(self.cycle_count).filter(|&cycles| cycles > 0)
+    }
 }
 
 impl VM {
@@ -148,12 +427,28 @@ impl VM {
             "stone" => Ok(VM::Stone),
             "stwo" => Ok(VM::Stwo),
             "openvm" => Ok(VM::OpenVM),
+            "native" => Ok(VM::Native),
             _ => Err(format!("Unknown VM: {}", vm_name)),
         }
     }
 }
 
+/// Program variants using accelerated instructions of the VM,
+/// appended to the name of the workload (e.g. `sha2-precompile`)
+pub const VARIANTS: [&str; 2] = ["precompile", "builtin"];
+
 impl BenchmarkName {
+    /// The workload and variant of the program, e.g. `("sha2-chain", Some("precompile"))`
+    pub fn workload(&self) -> (&str, Option<&str>) {
+        VARIANTS
+            .iter()
+            .find_map(|variant| {
+                let workload = self.program.strip_suffix(variant)?.strip_suffix('-')?;
+                Some((workload, Some(*variant)))
+            })
+            .unwrap_or((&self.program, None))
+    }
+
     pub fn parse(benchmark_name: &str) -> Result<BenchmarkName, String> {
         let parts: Vec<&str> = benchmark_name.splitn(2, '-').collect();
         if parts.len() != 2 {
@@ -173,6 +468,11 @@ pub fn size<T: Serialize>(item: &T) -> usize {
     bincode::serialized_size(item).unwrap() as usize
 }
 
+/// Lowercase hex encoding of public values
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -190,6 +490,10 @@ mod tests {
             verifier_durations: vec![Duration::from_millis(1234), Duration::from_millis(5678)],
             cycle_count: 987654,
             peak_memory: Some(1073741824), // 1GB
+            execution: None,
+            preprocessing: None,
+            wrap: None,
+            settings: BTreeMap::new(),
         };
 
         // Serialize to JSON
@@ -217,6 +521,10 @@ mod tests {
             verifier_durations: vec![Duration::from_millis(50)],
             cycle_count: 10000,
             peak_memory: None,
+            execution: None,
+            preprocessing: None,
+            wrap: None,
+            settings: BTreeMap::new(),
         };
 
         let json = result.to_json();
@@ -244,6 +552,10 @@ mod tests {
             verifier_durations: vec![Duration::from_millis(50)],
             cycle_count: 10000,
             peak_memory: Some(1234567),
+            execution: None,
+            preprocessing: None,
+            wrap: None,
+            settings: BTreeMap::new(),
         };
 
         let result2 = BenchmarkResult {
@@ -252,6 +564,10 @@ mod tests {
             verifier_durations: vec![Duration::from_millis(50)],
             cycle_count: 10000,
             peak_memory: Some(1234567),
+            execution: None,
+            preprocessing: None,
+            wrap: None,
+            settings: BTreeMap::new(),
         };
 
         let result3 = BenchmarkResult {
@@ -260,9 +576,113 @@ mod tests {
             verifier_durations: vec![Duration::from_millis(50)],
             cycle_count: 10000,
             peak_memory: Some(1234567),
+            execution: None,
+            preprocessing: None,
+            wrap: None,
+            settings: BTreeMap::new(),
         };
 
         assert_eq!(result1, result2);
         assert_ne!(result1, result3);
     }
+
+    #[test]
+    fn test_benchmark_workload() {
+        let workload = |name| BenchmarkName::parse(name).unwrap().workload().0.to_string();
+        let variant = |name| {
+            BenchmarkName::parse(name)
+                .unwrap()
+                .workload()
+                .1
+                .map(String::from)
+        };
+        assert_eq!(workload("sp1-sha2-chain-precompile"), "sha2-chain");
+        assert_eq!(
+            variant("sp1-sha2-chain-precompile").as_deref(),
+            Some("precompile")
+        );
+        assert_eq!(workload("stone-sha3-builtin"), "sha3");
+        assert_eq!(workload("jolt-mat-mul"), "mat-mul");
+        assert_eq!(variant("jolt-mat-mul"), None);
+    }
+
+    #[test]
+    fn test_execute_mode_id() {
+        let mut id = BenchmarkId {
+            name: BenchmarkName::parse("sp1-sha2-precompile").unwrap(),
+            n: 1 << 20,
+            backend_options: Default::default(),
+            mode: Mode::Execute,
+        };
+        assert_eq!(id.to_string(), "sp1-sha2-precompile-execute-n1048576");
+
+        // prove mode is left out of the id and the config
+        id.mode = Mode::Prove;
+        assert_eq!(id.to_string(), "sp1-sha2-precompile-n1048576");
+        let config = BenchmarkConfig {
+            n: 4096,
+            program: "fib".to_string(),
+            verifier_iterations: 1,
+            ..Default::default()
+        };
+        assert!(!serde_json::to_string(&config).unwrap().contains("mode"));
+    }
+
+    #[test]
+    fn test_execution_duration_json() {
+        let report = ExecutionReport {
+            duration: Duration::from_micros(1500),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&report).unwrap();
+        assert!(json.contains("\"execution_duration_ms\":1.5"), "{}", json);
+        let parsed: ExecutionReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.duration, report.duration);
+
+        // results written before durations were fractional
+        let parsed: ExecutionReport =
+            serde_json::from_str(r#"{"execution_duration_ms":12,"public_output":""}"#).unwrap();
+        assert_eq!(parsed.duration, Duration::from_millis(12));
+        assert!(parsed.syscall_counts.is_empty());
+        assert_eq!(parsed.gas, None);
+    }
+
+    #[test]
+    fn test_execution_counts_json() {
+        let report = ExecutionReport {
+            syscall_counts: [("SHA_EXTEND".to_string(), 4)].into(),
+            touched_memory_addresses: Some(1024),
+            ..Default::default()
+        };
+        let parsed: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string(&report).unwrap()).unwrap();
+        assert_eq!(parsed["syscall_counts"]["SHA_EXTEND"], 4);
+        assert_eq!(parsed["touched_memory_addresses"], 1024);
+        assert!(parsed.get("opcode_counts").is_none());
+        assert!(parsed.get("gas").is_none());
+    }
+
+    #[test]
+    fn test_preprocessing_json() {
+        let result = BenchmarkResult {
+            preprocessing: Some(PreprocessingReport {
+                compile_duration: Duration::from_millis(250),
+                prover_size: 1 << 20,
+                reused: true,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let parsed: serde_json::Value = serde_json::from_str(&result.to_json()).unwrap();
+        assert_eq!(parsed["preprocessing"]["compile_duration_ms"], 250.0);
+        assert_eq!(parsed["preprocessing"]["prover_size_bytes"], 1 << 20);
+        assert_eq!(parsed["preprocessing"]["reused"], true);
+        assert!(parsed["preprocessing"].get("peak_memory_bytes").is_none());
+
+        let roundtrip: BenchmarkResult = serde_json::from_str(&result.to_json()).unwrap();
+        assert_eq!(roundtrip, result);
+        assert!(!BenchmarkResult::default()
+            .to_json()
+            .contains("preprocessing"));
+    }
 }
@@ -1,15 +1,53 @@
-use std::fs;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
 
-use clap::{arg, Parser};
+use clap::{Args, Parser, Subcommand};
+use utils::compare::{Metric, Thresholds};
+use utils::matrix::{Manifest, SweepOptions};
+use utils::memory::Limits;
+use utils::pricing::Pricing;
+use utils::runner::{self, ResumePolicy, RunError, RunRequest};
+use utils::sampling::SamplingOptions;
+use utils::search::Search;
+use utils::session::SessionOptions;
 use utils::{
-    memory, BenchmarkConfig, BenchmarkConfigAndResult, BenchmarkId, BenchmarkName, BenchmarkResult,
-    BenchmarkResultWithMemory, BenchmarkStatus,
+    compare, export, fit, matrix, results, search, summary, BenchmarkConfigAndResult, BenchmarkId,
+    BenchmarkName, Mode,
 };
 
 /// A tool to build and optionally benchmark a cargo project
 #[derive(Parser, Debug)]
-#[clap()]
+#[command(args_conflicts_with_subcommands = true)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    run: Option<RunArgs>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run every benchmark described by a matrix manifest
+    RunMatrix(RunMatrixArgs),
+    /// Find the largest n of a benchmark that proves within a memory or time budget
+    Search(SearchArgs),
+    /// Compare two result sets and report regressions
+    Compare(CompareArgs),
+    /// Export all results as one flat row per run
+    Export(ExportArgs),
+    /// Render Markdown tables comparing the VMs on every workload
+    Summary(SummaryArgs),
+    /// Fit prover time, peak memory and proof size of every benchmark against n
+    Fit(FitArgs),
+}
+
+/// Run a single benchmark
+#[derive(Args, Debug)]
+struct RunArgs {
     /// The name of executable to run
     /// It is assumed to be located under the target/release directory
     #[arg(long)]
@@ -35,130 +73,527 @@ struct Cli {
     #[arg(long, required = false, default_value = "9", value_delimiter = ',')]
     allowed_signals: Vec<i32>,
 
+    /// Refuse to run the benchmark if the machine is noisy
+    /// (e.g. powersave governor, high load, competing processes)
+    #[arg(long)]
+    strict: bool,
+
+    #[command(flatten)]
+    limits: LimitArgs,
+
+    #[command(flatten)]
+    sampling: SamplingArgs,
+
+    /// Driver specific option in the form key=value,
+    /// passed to the benchmark binary as `--key value` (can be repeated)
+    #[arg(long = "backend-option", value_parser = parse_backend_option)]
+    backend_options: Vec<(String, String)>,
+
+    /// Prove the program, or only execute it to count cycles
+    #[arg(long, value_enum, default_value_t = Mode::Prove)]
+    mode: Mode,
+
+    #[command(flatten)]
+    resume: ResumeArgs,
+
     /// Arguments to pass to the benchmark binary
     #[arg(trailing_var_arg = true)]
     args: Vec<String>,
 }
 
+#[derive(Args, Debug)]
+struct RunMatrixArgs {
+    /// Path to the TOML manifest describing the sweep
+    manifest: PathBuf,
+
+    /// Abort the sweep if the machine is noisy
+    #[arg(long)]
+    strict: bool,
+
+    /// Memory and time limits, larger n of a benchmark
+    /// exceeding them are skipped afterwards
+    #[command(flatten)]
+    limits: LimitArgs,
+
+    #[command(flatten)]
+    sampling: SamplingArgs,
+
+    #[command(flatten)]
+    session: SessionArgs,
+
+    #[command(flatten)]
+    resume: ResumeArgs,
+}
+
+#[derive(Args, Debug)]
+struct SessionArgs {
+    /// Run every benchmark this many times in a shuffled order,
+    /// the samples of all trials are merged into one result
+    #[arg(long)]
+    trials: Option<u32>,
+
+    /// Seed of the shuffled run order (default: random)
+    #[arg(long, requires = "trials")]
+    seed: Option<u64>,
+}
+
+impl SessionArgs {
+    fn session(&self) -> Option<SessionOptions> {
+        self.trials.map(|trials| SessionOptions {
+            trials,
+            seed: self.seed.unwrap_or_else(rand::random),
+        })
+    }
+}
+
+#[derive(Args, Debug)]
+struct SearchArgs {
+    /// Name of the benchmark (e.g. sp1-sha2-precompile)
+    bench_name: String,
+
+    /// First n to probe, doubled until a probe fails
+    #[arg(long, default_value = "1", value_parser = clap::value_parser!(u32).range(1..))]
+    start_n: u32,
+
+    /// Never probe beyond this n
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    max_n: Option<u32>,
+
+    /// Stop once the largest proven and the smallest failed n are this close
+    #[arg(long, default_value = "1")]
+    resolution: u32,
+
+    /// Number of verifier iterations to run (default: 1)
+    #[arg(long, default_value = "1")]
+    verifier_iterations: u32,
+
+    /// Driver specific option in the form key=value (can be repeated)
+    #[arg(long = "backend-option", value_parser = parse_backend_option)]
+    backend_options: Vec<(String, String)>,
+
+    /// Search the largest provable, or only executable n
+    #[arg(long, value_enum, default_value_t = Mode::Prove)]
+    mode: Mode,
+
+    /// Abort the search if the machine is noisy
+    #[arg(long)]
+    strict: bool,
+
+    #[command(flatten)]
+    limits: LimitArgs,
+
+    #[command(flatten)]
+    resume: ResumeArgs,
+}
+
+#[derive(Args, Debug)]
+struct CompareArgs {
+    /// Directory of the baseline results
+    before: PathBuf,
+
+    /// Directory of the new results
+    after: PathBuf,
+
+    /// Largest tolerated increase of every metric in percent, exceeding it
+    /// or a benchmark failing now exits with an error (default: only report changes)
+    #[arg(long, value_name = "PERCENT")]
+    threshold: Option<f64>,
+
+    /// Largest tolerated increase of the prover time in percent
+    #[arg(long, value_name = "PERCENT")]
+    prover_time_threshold: Option<f64>,
+
+    /// Largest tolerated increase of the verifier time in percent
+    #[arg(long, value_name = "PERCENT")]
+    verifier_time_threshold: Option<f64>,
+
+    /// Largest tolerated increase of the proof size in percent
+    #[arg(long, value_name = "PERCENT")]
+    proof_size_threshold: Option<f64>,
+
+    /// Largest tolerated increase of the cycle count in percent
+    #[arg(long, value_name = "PERCENT")]
+    cycles_threshold: Option<f64>,
+
+    /// Largest tolerated increase of the peak memory in percent
+    #[arg(long, value_name = "PERCENT")]
+    peak_memory_threshold: Option<f64>,
+
+    /// Significance level below which a change of the timings is
+    /// considered real rather than noise
+    #[arg(long, default_value = "0.05")]
+    alpha: f64,
+
+    /// Exit with an error if a benchmark fails now, even without thresholds
+    #[arg(long)]
+    fail_on_broken: bool,
+}
+
+impl CompareArgs {
+    fn thresholds(&self) -> Thresholds {
+        let max_increase: BTreeMap<Metric, f64> = Metric::ALL
+            .into_iter()
+            .filter_map(|metric| {
+                let percent = match metric {
+                    Metric::ProverTime => self.prover_time_threshold,
+                    Metric::VerifierTime => self.verifier_time_threshold,
+                    Metric::ProofSize => self.proof_size_threshold,
+                    Metric::Cycles => self.cycles_threshold,
+                    Metric::PeakMemory => self.peak_memory_threshold,
+                }
+                .or(self.threshold)?;
+                Some((metric, percent / 100.0))
+            })
+            .collect();
+        Thresholds {
+            // any threshold gates the comparison, so a broken benchmark fails it too
+            fail_on_broken: self.fail_on_broken || !max_increase.is_empty(),
+            max_increase,
+            alpha: self.alpha,
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+struct ExportArgs {
+    /// Directory of the results (default: benchmark_results of the repository)
+    #[arg(long)]
+    results_dir: Option<PathBuf>,
+
+    #[arg(long, value_enum, default_value_t = export::Format::Csv)]
+    format: export::Format,
+
+    /// File to write to (default: stdout)
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct SummaryArgs {
+    /// Directory of the results (default: benchmark_results of the repository)
+    #[arg(long)]
+    results_dir: Option<PathBuf>,
+
+    /// Only summarize these workloads (e.g. sha2,ec)
+    #[arg(long, value_delimiter = ',')]
+    workload: Vec<String>,
+
+    /// TOML table of instance prices (see pricing.toml),
+    /// adds the estimated cost per proof on every instance
+    #[arg(long)]
+    pricing: Option<PathBuf>,
+
+    /// File to write to (default: stdout)
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct FitArgs {
+    /// Directory of the results (default: benchmark_results of the repository)
+    #[arg(long)]
+    results_dir: Option<PathBuf>,
+
+    /// Only fit these workloads (e.g. sha2,ec)
+    #[arg(long, value_delimiter = ',')]
+    workload: Vec<String>,
+
+    /// Extrapolate every fit to this n
+    #[arg(long)]
+    at: Option<u32>,
+
+    /// Flag measured points deviating from the fit by more than this many percent
+    #[arg(long, value_name = "PERCENT", default_value = "25")]
+    deviation: f64,
+}
+
+#[derive(Args, Debug)]
+struct LimitArgs {
+    /// Kill the benchmark binary if it runs longer than this many seconds
+    #[arg(long, value_name = "SECONDS")]
+    timeout: Option<u64>,
+
+    /// Memory limit of the benchmark binary and its children (not the runner),
+    /// in bytes or with a K, M, G or T suffix (e.g. 64G), exceeding it counts as OOM
+    #[arg(long, value_name = "BYTES", value_parser = parse_bytes)]
+    memory_max: Option<u64>,
+}
+
+impl LimitArgs {
+    fn limits(&self) -> Limits {
+        Limits {
+            timeout: self.timeout.map(Duration::from_secs),
+            memory_max: self.memory_max,
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+struct SamplingArgs {
+    /// Run the benchmark binary up to this many times while the prover or
+    /// verifier samples are noisy, the samples of all runs are merged
+    #[arg(long)]
+    max_runs: Option<u32>,
+
+    /// Run the benchmark binary at least this many times (at most --max-runs),
+    /// by default it only runs again while the samples are noisy
+    #[arg(
+        long,
+        default_value = "1",
+        value_parser = clap::value_parser!(u32).range(1..),
+        requires = "max_runs"
+    )]
+    min_runs: u32,
+
+    /// Samples whose coefficient of variation exceeds this are noisy
+    #[arg(
+        long,
+        value_name = "PERCENT",
+        default_value = "5",
+        requires = "max_runs"
+    )]
+    max_cv: f64,
+
+    /// Noisy samples are taken until the 95% confidence interval
+    /// of their mean is within this many percent of the mean
+    #[arg(
+        long,
+        value_name = "PERCENT",
+        default_value = "5",
+        requires = "max_runs"
+    )]
+    target_ci: f64,
+}
+
+impl SamplingArgs {
+    fn sampling(&self) -> Option<SamplingOptions> {
+        self.max_runs.map(|max_runs| SamplingOptions {
+            max_cv: self.max_cv / 100.0,
+            target_ci: self.target_ci / 100.0,
+            min_runs: self.min_runs,
+            max_runs,
+        })
+    }
+}
+
+#[derive(Args, Debug)]
+struct ResumeArgs {
+    /// Skip benchmarks that already have a result produced with the
+    /// same config, by the same driver build on this machine
+    #[arg(long)]
+    resume: bool,
+
+    /// When resuming, run benchmarks again whose existing result is a failure
+    #[arg(long, requires = "resume")]
+    retry_failed: bool,
+
+    /// When resuming, always run these benchmarks again (e.g. sp1-fib-n4096)
+    #[arg(long, value_delimiter = ',', requires = "resume")]
+    force: Vec<String>,
+}
+
+impl ResumeArgs {
+    fn policy(self) -> Option<ResumePolicy> {
+        self.resume.then_some(ResumePolicy {
+            retry_failed: self.retry_failed,
+            force: self.force,
+        })
+    }
+}
+
+fn parse_backend_option(option: &str) -> Result<(String, String), String> {
+    option
+        .split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("Invalid backend option: {}. Expected key=value", option))
+}
+
+fn parse_bytes(bytes: &str) -> Result<u64, String> {
+    let (digits, unit) = match bytes.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => bytes.split_at(index),
+        None => (bytes, ""),
+    };
+    let multiplier: u64 = match unit.to_ascii_uppercase().as_str() {
+        "" => 1,
+        "K" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        "T" => 1 << 40,
+        _ => return Err(format!("Invalid unit: {}. Expected K, M, G or T", unit)),
+    };
+    digits
+        .parse::<u64>()
+        .ok()
+        .and_then(|value| value.checked_mul(multiplier))
+        .ok_or_else(|| format!("Invalid size: {}", bytes))
+}
+
 fn main() {
     let cli = Cli::parse();
-    let root_folder = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
-        .parent()
-        .unwrap();
-    let bench_arg = cli.bench_arg;
 
+    let outcome = match (cli.command, cli.run) {
+        (Some(Command::RunMatrix(args)), _) => run_matrix(args),
+        (Some(Command::Search(args)), _) => run_search(args),
+        (Some(Command::Compare(args)), _) => run_compare(args),
+        (Some(Command::Export(args)), _) => run_export(args),
+        (Some(Command::Summary(args)), _) => run_summary(args),
+        (Some(Command::Fit(args)), _) => run_fit(args),
+        (None, Some(args)) => run_single(args),
+        (None, None) => {
+            eprintln!("Nothing to do, see --help");
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(e) = outcome {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run_single(args: RunArgs) -> Result<(), RunError> {
     // Parse benchmark ID from benchmark name
     let name =
-        BenchmarkName::parse(&cli.bench_name).expect("Failed to parse benchmark ID from name");
+        BenchmarkName::parse(&args.bench_name).expect("Failed to parse benchmark ID from name");
 
-    // Identifier is (vm, program, n)
+    // Identifier is (vm, program, n), optional backend options and the mode
     let ident = BenchmarkId {
-        name: name.clone(),
-        n: bench_arg,
+        name,
+        n: args.bench_arg,
+        backend_options: args.backend_options.into_iter().collect::<BTreeMap<_, _>>(),
+        mode: args.mode,
     };
 
-    // Create benchmark_results directory if it doesn't exist
-    let results_dir = root_folder.join("benchmark_results");
-    fs::create_dir_all(&results_dir).expect("Failed to create benchmark_results directory");
-
-    // Prepare benchmark arguments
-    let mut benchmark_args = vec![
-        "--n".to_string(),
-        bench_arg.to_string(),
-        "--verifier-iterations".to_string(),
-        cli.verifier_iterations.to_string(),
-    ];
-    benchmark_args.extend(cli.args);
-
-    // Run the benchmark binary in a seperate cgroup
-    let mem_usage = memory::run_with_memory_tracking(
-        &cli.allowed_exit_codes,
-        &cli.allowed_signals,
-        &cli.bin,
-        &benchmark_args,
-    )
-    .unwrap();
-
-    // handle benchmark result (success or failure)
-    let config = BenchmarkConfig {
-        n: bench_arg,
-        program: name.program.clone(),
-        verifier_iterations: cli.verifier_iterations,
+    let request = RunRequest {
+        id: ident,
+        bin: args.bin,
+        working_dir: None,
+        verifier_iterations: args.verifier_iterations,
+        allowed_exit_codes: args.allowed_exit_codes,
+        allowed_signals: args.allowed_signals,
+        limits: args.limits.limits(),
+        strict: args.strict,
+        sampling: args.sampling.sampling(),
+        args: args.args,
     };
 
-    let result = if mem_usage.result.is_ok() {
-        // Benchmark succeeded, read the temporary results.json file
-        let file_contents = std::fs::read_to_string("results.json").unwrap();
-
-        // Set peak memory
-        let result = BenchmarkResultWithMemory {
-            result: serde_json::from_str::<BenchmarkResult>(&file_contents).unwrap(),
-            peak_memory: mem_usage.memory,
-        };
-
-        // print an overview for debugging
-        {
-            let prover_times: String = result
-                .result
-                .prover_durations
-                .iter()
-                .map(|d| d.as_secs().to_string())
-                .collect::<Vec<String>>()
-                .join(", ");
-
-            let verifier_times: String = result
-                .result
-                .verifier_durations
-                .iter()
-                .map(|d| d.as_millis().to_string())
-                .collect::<Vec<String>>()
-                .join(", ");
-
-            fn fmt(bytes: u64) -> String {
-                const KB: u64 = 1000;
-                const MB: u64 = KB * 1000;
-                const GB: u64 = MB * 1000;
-                if bytes >= GB {
-                    format!("{:.2} GB", bytes as f64 / GB as f64)
-                } else if bytes >= MB {
-                    format!("{:.2} MB", bytes as f64 / MB as f64)
-                } else if bytes >= KB {
-                    format!("{:.2} KB", bytes as f64 / KB as f64)
-                } else {
-                    format!("{} B", bytes)
-                }
-            }
-
-            println!("Results of {}", ident);
-            println!("  Proof Size    : {}", fmt(result.result.proof_size as u64));
-            println!("  Peak Memory   : {}", fmt(result.peak_memory));
-            println!("  Cycles Count  : {}", result.result.cycle_count);
-            println!("  Prover Time   : {} sec", prover_times);
-            println!("  Verifier Time : {} ms", verifier_times);
-        };
-
-        // return successful benchmark result
-        BenchmarkConfigAndResult {
-            vm: name.vm,
-            config,
-            result: BenchmarkStatus::Success(result),
-        }
-    } else {
-        // print error message
-        eprintln!("Benchmark failed with: {:?}", mem_usage.result);
-        BenchmarkConfigAndResult {
-            vm: name.vm,
-            config,
-            result: BenchmarkStatus::Failure(mem_usage.result),
+    let results_dir = runner::results_dir();
+    if let Some(resume) = args.resume.policy() {
+        if resume.completed(&request, &results_dir, None).is_some() {
+            println!("Skipping {}: already completed", request.id);
+            return Ok(());
         }
+    }
+
+    runner::run(&request, &results_dir)?;
+    Ok(())
+}
+
+fn run_matrix(args: RunMatrixArgs) -> Result<(), RunError> {
+    let manifest = Manifest::load(&args.manifest).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    });
+    let options = SweepOptions {
+        strict: args.strict,
+        resume: args.resume.policy(),
+        limits: args.limits.limits(),
+        session: args.session.session(),
+        sampling: args.sampling.sampling(),
+    };
+    matrix::run(&manifest, &options)
+}
+
+fn run_search(args: SearchArgs) -> Result<(), RunError> {
+    let name = BenchmarkName::parse(&args.bench_name).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    });
+    if !name.vm.driver().supports(&name.program) {
+        eprintln!("{} is not supported by the driver", name);
+        std::process::exit(2);
+    }
+    if args.limits.timeout.is_none() && args.limits.memory_max.is_none() && args.max_n.is_none() {
+        eprintln!("Nothing bounds the search, pass --memory-max, --timeout or --max-n");
+        std::process::exit(2);
+    }
+
+    let search = Search {
+        name,
+        backend_options: args.backend_options.into_iter().collect(),
+        mode: args.mode,
+        verifier_iterations: args.verifier_iterations,
+        start_n: args.start_n,
+        max_n: args.max_n,
+        resolution: args.resolution,
+    };
+    let options = SweepOptions {
+        strict: args.strict,
+        resume: args.resume.policy(),
+        limits: args.limits.limits(),
+        session: None,
+        sampling: None,
     };
+    search::run(&search, &options)?;
+    Ok(())
+}
+
+fn run_compare(args: CompareArgs) -> Result<(), RunError> {
+    let before = results::load(&args.before)?;
+    let after = results::load(&args.after)?;
+    let report = compare::compare(&before, &after, &args.thresholds());
+    report.print();
+    if report.regressions() > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn run_export(args: ExportArgs) -> Result<(), RunError> {
+    let results_dir = args.results_dir.unwrap_or_else(runner::results_dir);
+    let results = results::load(&results_dir)?;
+    match args.output {
+        Some(path) => export::write(&results, args.format, File::create(path)?)?,
+        None => export::write(&results, args.format, io::stdout().lock())?,
+    }
+    Ok(())
+}
+
+fn run_summary(args: SummaryArgs) -> Result<(), RunError> {
+    let results = load_results(args.results_dir, &args.workload)?;
+    let pricing = args.pricing.map(|path| {
+        Pricing::load(&path).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        })
+    });
+    let markdown = summary::render(&results, pricing.as_ref());
+    match args.output {
+        Some(path) => fs::write(path, markdown)?,
+        None => print!("{}", markdown),
+    }
+    Ok(())
+}
 
-    // Save JSON to permanent location
-    let json_filename = format!("{}.json", ident);
-    let json_path = results_dir.join(json_filename);
-    fs::write(&json_path, result.to_json()).unwrap();
-    println!("Results saved to: {}", json_path.display());
+fn run_fit(args: FitArgs) -> Result<(), RunError> {
+    let results = load_results(args.results_dir, &args.workload)?;
+    let analyses = fit::analyze(&results, args.at, args.deviation / 100.0);
+    fit::print(&analyses);
+    Ok(())
+}
 
-    // Remove temporary json file if it exists
-    let _ = fs::remove_file("results.json");
+/// Results of `results_dir` (or the repository), only of the given workloads if any
+fn load_results(
+    results_dir: Option<PathBuf>,
+    workloads: &[String],
+) -> io::Result<Vec<BenchmarkConfigAndResult>> {
+    let results_dir = results_dir.unwrap_or_else(runner::results_dir);
+    let mut results = results::load(&results_dir)?;
+    if !workloads.is_empty() {
+        results.retain(|result| {
+            let workload = result.id().name.workload().0.to_string();
+            workloads.contains(&workload)
+        });
+    }
+    Ok(results)
 }
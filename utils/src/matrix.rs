@@ -0,0 +1,616 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::memory::{Limits, MemoryResult};
+use crate::runner::{self, ResumePolicy, RunError, RunRequest};
+use crate::sampling::SamplingOptions;
+use crate::session::SessionOptions;
+use crate::{BenchmarkId, BenchmarkName, BenchmarkStatus, Mode, SkippedAfterFailure, VM};
+
+/// A declarative description of a benchmark sweep, e.g.
+///
+/// ```toml
+/// verifier_iterations = 20
+///
+/// [[benchmark]]
+/// vms = ["sp1", "risc0", "openvm"]
+/// programs = ["sha2", "sha2-chain"]
+/// variants = ["precompile"]
+/// n = [4096, 8192]
+///
+/// [benchmark.backend_options]
+/// proof-mode = ["core", "compressed"]
+/// ```
+///
+/// Every entry expands to the cartesian product of its
+/// VMs, programs, variants, backend options and n values.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    /// Default number of verifier iterations
+    #[serde(default = "default_verifier_iterations")]
+    pub verifier_iterations: u32,
+    #[serde(rename = "benchmark", default)]
+    pub benchmarks: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestEntry {
+    pub vms: Vec<VM>,
+    pub programs: Vec<String>,
+    /// Suffixes appended to every program (e.g. "precompile" turns "sha2"
+    /// into "sha2-precompile"), the empty string selects the plain program
+    #[serde(default = "default_variants")]
+    pub variants: Vec<String>,
+    pub n: Vec<u32>,
+    /// Overrides the default number of verifier iterations
+    pub verifier_iterations: Option<u32>,
+    /// Every combination of values is run, each passed
+    /// to the driver as `--<key> <value>`
+    #[serde(default)]
+    pub backend_options: BTreeMap<String, Vec<OptionValue>>,
+    /// `execute` only runs the programs, reporting cycles but no proof
+    #[serde(default)]
+    pub mode: Mode,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum OptionValue {
+    Integer(i64),
+    Bool(bool),
+    String(String),
+}
+
+impl std::fmt::Display for OptionValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OptionValue::Integer(value) => write!(f, "{}", value),
+            OptionValue::Bool(value) => write!(f, "{}", value),
+            OptionValue::String(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+fn default_verifier_iterations() -> u32 {
+    1
+}
+
+fn default_variants() -> Vec<String> {
+    vec![String::new()]
+}
+
+/// A single benchmark of an expanded manifest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatrixRun {
+    pub id: BenchmarkId,
+    pub verifier_iterations: u32,
+}
+
+impl Manifest {
+    pub fn parse(content: &str) -> Result<Manifest, String> {
+        toml::from_str(content).map_err(|e| format!("Invalid manifest: {}", e))
+    }
+
+    pub fn load(path: &Path) -> Result<Manifest, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Manifest::parse(&content)
+    }
+
+    /// Expand the manifest into the list of benchmarks to run,
+    /// in manifest order and without duplicates. The n values
+    /// of every entry are run in ascending order.
+    ///
+    /// Programs the driver of a VM does not support are skipped with a warning.
+    pub fn expand(&self) -> Vec<MatrixRun> {
+        let mut seen = HashSet::new();
+        let mut runs = Vec::new();
+        for entry in &self.benchmarks {
+            let verifier_iterations = entry
+                .verifier_iterations
+                .unwrap_or(self.verifier_iterations);
+            let backend_options = option_combinations(&entry.backend_options);
+            let mut n_values = entry.n.clone();
+            n_values.sort_unstable();
+            for vm in &entry.vms {
+                let driver = vm.driver();
+                for program in &entry.programs {
+                    for variant in &entry.variants {
+                        let program = if variant.is_empty() {
+                            program.clone()
+                        } else {
+                            format!("{}-{}", program, variant)
+                        };
+                        if !driver.supports(&program) {
+                            eprintln!("Skipping {}-{}: not supported by the driver", vm, program);
+                            continue;
+                        }
+                        for options in &backend_options {
+                            for &n in &n_values {
+                                let id = BenchmarkId {
+                                    name: BenchmarkName {
+                                        vm: *vm,
+                                        program: program.clone(),
+                                    },
+                                    n,
+                                    backend_options: options.clone(),
+                                    mode: entry.mode,
+                                };
+                                if seen.insert(id.clone()) {
+                                    runs.push(MatrixRun {
+                                        id,
+                                        verifier_iterations,
+                                    });
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        runs
+    }
+}
+
+/// Cartesian product of all backend option values
+fn option_combinations(
+    options: &BTreeMap<String, Vec<OptionValue>>,
+) -> Vec<BTreeMap<String, String>> {
+    let mut combinations = vec![BTreeMap::new()];
+    for (key, values) in options {
+        combinations = combinations
+            .into_iter()
+            .flat_map(|combination| {
+                values.iter().map(move |value| {
+                    let mut combination = combination.clone();
+                    combination.insert(key.clone(), value.to_string());
+                    combination
+                })
+            })
+            .collect();
+    }
+    combinations
+}
+
+impl MatrixRun {
+    /// How to invoke the driver for this run
+    pub fn request(&self, root: &Path, options: &SweepOptions) -> RunRequest {
+        let name = &self.id.name;
+        let driver = name.vm.driver();
+        RunRequest {
+            id: self.id.clone(),
+            bin: driver.bin.to_string(),
+            working_dir: Some(driver.working_dir(root, &name.program)),
+            verifier_iterations: self.verifier_iterations,
+            allowed_exit_codes: driver.allowed_exit_codes.to_vec(),
+            // SIGKILL (9) for OOM
+            allowed_signals: vec![9],
+            limits: options.limits,
+            strict: options.strict,
+            sampling: options.sampling,
+            args: driver.program_args(&name.program),
+        }
+    }
+}
+
+/// How a sweep is run
+#[derive(Debug, Clone, Default)]
+pub struct SweepOptions {
+    /// Abort the sweep if the machine is noisy
+    pub strict: bool,
+    /// Skip benchmarks that already have a matching result
+    pub resume: Option<ResumePolicy>,
+    /// Memory and time limits of every benchmark
+    pub limits: Limits,
+    /// Run every benchmark several times in a shuffled order
+    pub session: Option<SessionOptions>,
+    /// Run a benchmark again while its samples are noisy
+    pub sampling: Option<SamplingOptions>,
+}
+
+/// Runs that exceeded the memory or time limits, by benchmark
+/// (vm, program and variant), backend options and mode.
+///
+/// Once a run exceeds the limits, larger n of the same benchmark
+/// are bound to exceed them as well and are not run.
+#[derive(Debug, Default)]
+pub struct ExceededLimits {
+    failures: HashMap<BenchmarkId, (BenchmarkId, MemoryResult)>,
+}
+
+/// Runs that only differ in n share the same family
+pub(crate) fn family(id: &BenchmarkId) -> BenchmarkId {
+    BenchmarkId { n: 0, ..id.clone() }
+}
+
+impl ExceededLimits {
+    /// Remember the run if its result exceeds the limits
+    pub fn record(&mut self, id: &BenchmarkId, status: &BenchmarkStatus) {
+        let (failed_run, failure) = match status {
+            BenchmarkStatus::Failure(result) if result.exceeds_limits() => (id.clone(), *result),
+            BenchmarkStatus::Skipped(skipped) => (skipped.failed_run.clone(), skipped.failure),
+            _ => return,
+        };
+        let key = family(id);
+        match self.failures.get(&key) {
+            Some((smallest, _)) if smallest.n <= failed_run.n => {}
+            _ => {
+                self.failures.insert(key, (failed_run, failure));
+            }
+        }
+    }
+
+    /// Why `id` should not be run, if a smaller n already exceeded the limits
+    pub fn skipped(&self, id: &BenchmarkId) -> Option<SkippedAfterFailure> {
+        let (failed_run, failure) = self.failures.get(&family(id))?;
+        (failed_run.n < id.n).then(|| SkippedAfterFailure {
+            failed_run: failed_run.clone(),
+            failure: *failure,
+        })
+    }
+}
+
+/// Indices of the runs that already have a result when resuming, every trial
+/// of a session has to be saved for it to count. The results are recorded
+/// in `exceeded`, so that larger n are skipped right away.
+fn completed_runs(
+    runs: &[MatrixRun],
+    options: &SweepOptions,
+    results_dir: &Path,
+    exceeded: &mut ExceededLimits,
+) -> HashSet<usize> {
+    let Some(resume) = &options.resume else {
+        return HashSet::new();
+    };
+    let root = runner::repository_root();
+    let trials = options.session.map(|session| session.trials);
+    let mut completed = HashSet::new();
+    for (index, run) in runs.iter().enumerate() {
+        let request = run.request(&root, options);
+        if let Some(existing) = resume.completed(&request, results_dir, trials) {
+            exceeded.record(&run.id, &existing.result);
+            completed.insert(index);
+        }
+    }
+    completed
+}
+
+/// Run every benchmark of the manifest one after the other.
+///
+/// A benchmark whose driver fails unexpectedly is reported and skipped,
+/// the sweep only aborts if the machine is too noisy in strict mode.
+/// After a run exceeds the memory or time limits, larger n of the
+/// same benchmark are recorded as skipped instead of being run.
+///
+/// In a session, every benchmark runs several times in a shuffled order
+/// and its result is saved with the samples of all trials so far.
+pub fn run(manifest: &Manifest, options: &SweepOptions) -> Result<(), RunError> {
+    let root = runner::repository_root();
+    let results_dir = runner::results_dir();
+    let runs = manifest.expand();
+    let schedule = match &options.session {
+        Some(session) => {
+            println!(
+                "Session of {} trials with seed {}",
+                session.trials, session.seed
+            );
+            let ids: Vec<BenchmarkId> = runs.iter().map(|run| run.id.clone()).collect();
+            session.schedule(&ids)
+        }
+        None => (0..runs.len()).map(|index| (index, 0)).collect(),
+    };
+    let total = schedule.len();
+
+    let mut failed = Vec::new();
+    let mut exceeded = ExceededLimits::default();
+    let completed = completed_runs(&runs, options, &results_dir, &mut exceeded);
+    let mut skipped = 0;
+    let mut trials: HashMap<usize, Vec<_>> = HashMap::new();
+    for (position, &(index, trial)) in schedule.iter().enumerate() {
+        let run = &runs[index];
+        let request = run.request(&root, options);
+        if completed.contains(&index) {
+            println!(
+                "[{}/{}] Skipping {}: already completed",
+                position + 1,
+                total,
+                run.id
+            );
+            continue;
+        }
+        if let Some(skip) = exceeded.skipped(&run.id) {
+            println!(
+                "[{}/{}] Skipping {}: {} exceeded the limits",
+                position + 1,
+                total,
+                run.id,
+                skip.failed_run
+            );
+            // keep the samples of earlier trials instead of overwriting them
+            if !trials.contains_key(&index) {
+                runner::skip(&request, &results_dir, skip)?;
+            }
+            skipped += 1;
+            continue;
+        }
+        match &options.session {
+            Some(session) => println!(
+                "[{}/{}] Running {} (trial {}/{})",
+                position + 1,
+                total,
+                run.id,
+                trial + 1,
+                session.trials
+            ),
+            None => println!("[{}/{}] Running {}", position + 1, total, run.id),
+        }
+        match runner::run(&request, &results_dir) {
+            Ok(result) => {
+                exceeded.record(&run.id, &result.result);
+                if let Some(session) = &options.session {
+                    let trials = trials.entry(index).or_default();
+                    trials.push((position, result));
+                    runner::save(&results_dir, &session.merge(trials))?;
+                }
+            }
+            Err(e @ RunError::NoisyMachine(_)) => return Err(e),
+            Err(e) => {
+                eprintln!("[{}/{}] {} failed: {}", position + 1, total, run.id, e);
+                failed.push(run.id.clone());
+            }
+        }
+    }
+
+    println!("Finished {} benchmarks", total);
+    if skipped > 0 {
+        println!("{} benchmarks skipped after exceeding the limits", skipped);
+    }
+    if !failed.is_empty() {
+        eprintln!("{} benchmarks could not be run:", failed.len());
+        for id in failed {
+            eprintln!("  {}", id);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BenchmarkConfigAndResult;
+
+    #[test]
+    fn test_manifest_expansion() {
+        let manifest = Manifest::parse(
+            r#"
+            verifier_iterations = 20
+
+            [[benchmark]]
+            vms = ["sp1", "jolt"]
+            programs = ["sha2"]
+            variants = ["", "precompile"]
+            n = [256, 512]
+            "#,
+        )
+        .unwrap();
+
+        let ids: Vec<String> = manifest
+            .expand()
+            .iter()
+            .map(|run| run.id.to_string())
+            .collect();
+
+        // jolt has no precompile variant of sha2
+        assert_eq!(
+            ids,
+            vec![
+                "sp1-sha2-n256",
+                "sp1-sha2-n512",
+                "sp1-sha2-precompile-n256",
+                "sp1-sha2-precompile-n512",
+                "jolt-sha2-n256",
+                "jolt-sha2-n512",
+            ]
+        );
+        assert!(manifest
+            .expand()
+            .iter()
+            .all(|run| run.verifier_iterations == 20));
+    }
+
+    #[test]
+    fn test_manifest_backend_options() {
+        let manifest = Manifest::parse(
+            r#"
+            [[benchmark]]
+            vms = ["sp1"]
+            programs = ["fib"]
+            n = [4096]
+            verifier_iterations = 3
+
+            [benchmark.backend_options]
+            proof-mode = ["core", "compressed"]
+            shard-size = [1048576]
+            "#,
+        )
+        .unwrap();
+
+        let runs = manifest.expand();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(
+            runs[0].id.to_string(),
+            "sp1-fib-proof-mode=core,shard-size=1048576-n4096"
+        );
+        assert_eq!(runs[0].verifier_iterations, 3);
+        assert_eq!(
+            runner::backend_args(&runs[1].id),
+            vec!["--proof-mode", "compressed", "--shard-size", "1048576"]
+        );
+    }
+
+    #[test]
+    fn test_manifest_execute_mode() {
+        let manifest = Manifest::parse(
+            r#"
+            [[benchmark]]
+            vms = ["risc0"]
+            programs = ["sha2"]
+            n = [1048576]
+            mode = "execute"
+            "#,
+        )
+        .unwrap();
+
+        let runs = manifest.expand();
+        assert_eq!(runs[0].id.to_string(), "risc0-sha2-execute-n1048576");
+        assert_eq!(
+            runs[0]
+                .request(Path::new("/"), &Default::default())
+                .config()
+                .mode,
+            Mode::Execute
+        );
+    }
+
+    #[test]
+    fn test_example_manifests_parse() {
+        let dir = runner::repository_root().join("matrix");
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            let manifest = Manifest::load(&path).unwrap();
+            assert!(!manifest.expand().is_empty(), "{}", path.display());
+        }
+    }
+
+    #[test]
+    fn test_skip_larger_n_after_exceeding_limits() {
+        let id = |program: &str, n| BenchmarkId {
+            name: BenchmarkName {
+                vm: VM::SP1,
+                program: program.to_string(),
+            },
+            n,
+            backend_options: Default::default(),
+            mode: Mode::Prove,
+        };
+
+        let mut exceeded = ExceededLimits::default();
+        exceeded.record(
+            &id("fib", 1024),
+            &BenchmarkStatus::Success(Default::default()),
+        );
+        exceeded.record(
+            &id("sha2", 1024),
+            &BenchmarkStatus::Failure(MemoryResult::Exited(101)),
+        );
+        assert!(exceeded.skipped(&id("fib", 2048)).is_none());
+        assert!(exceeded.skipped(&id("sha2", 2048)).is_none());
+
+        exceeded.record(
+            &id("fib", 2048),
+            &BenchmarkStatus::Failure(MemoryResult::Timeout(3600)),
+        );
+        assert!(exceeded.skipped(&id("fib", 2048)).is_none());
+        let skipped = exceeded.skipped(&id("fib", 4096)).unwrap();
+        assert_eq!(skipped.failed_run, id("fib", 2048));
+        assert_eq!(skipped.failure, MemoryResult::Timeout(3600));
+
+        // other programs and backend options are unaffected
+        assert!(exceeded.skipped(&id("fib-precompile", 4096)).is_none());
+        let mut other = id("fib", 4096);
+        other
+            .backend_options
+            .insert("proof-mode".into(), "core".into());
+        assert!(exceeded.skipped(&other).is_none());
+        let mut execute = id("fib", 4096);
+        execute.mode = Mode::Execute;
+        assert!(exceeded.skipped(&execute).is_none());
+    }
+
+    #[test]
+    fn test_resume_session_needs_all_trials() {
+        let results_dir = std::env::temp_dir().join(format!("session-{}", std::process::id()));
+        fs::create_dir_all(&results_dir).unwrap();
+        let manifest = Manifest::parse(
+            r#"
+            [[benchmark]]
+            vms = ["sp1"]
+            programs = ["fib"]
+            n = [1024, 2048, 4096]
+            "#,
+        )
+        .unwrap();
+        let runs = manifest.expand();
+        let session = SessionOptions {
+            trials: 3,
+            seed: 42,
+        };
+        let options = SweepOptions {
+            resume: Some(ResumePolicy::default()),
+            session: Some(session),
+            ..Default::default()
+        };
+        let root = runner::repository_root();
+        let save = |run: &MatrixRun, trials: usize, result: BenchmarkStatus| {
+            let request = run.request(&root, &options);
+            let trial = BenchmarkConfigAndResult {
+                vm: VM::SP1,
+                config: request.config(),
+                result,
+                hygiene: None,
+                provenance: Some(request.provenance(&[])),
+                session: None,
+                sampling: None,
+            };
+            let trials: Vec<_> = (0..trials)
+                .map(|position| (position, trial.clone()))
+                .collect();
+            runner::save(&results_dir, &session.merge(&trials)).unwrap();
+        };
+
+        // the first trial alone does not complete the session
+        save(&runs[0], 1, BenchmarkStatus::Success(Default::default()));
+        save(
+            &runs[1],
+            1,
+            BenchmarkStatus::Failure(MemoryResult::Timeout(3600)),
+        );
+        let mut exceeded = ExceededLimits::default();
+        assert!(completed_runs(&runs, &options, &results_dir, &mut exceeded).is_empty());
+        assert!(exceeded.skipped(&runs[2].id).is_none());
+
+        // once all trials are saved, they are not run again
+        save(&runs[0], 3, BenchmarkStatus::Success(Default::default()));
+        save(
+            &runs[1],
+            3,
+            BenchmarkStatus::Failure(MemoryResult::Timeout(3600)),
+        );
+        let completed = completed_runs(&runs, &options, &results_dir, &mut exceeded);
+        assert_eq!(completed, HashSet::from([0, 1]));
+        assert!(exceeded.skipped(&runs[2].id).is_some());
+
+        // a session of a different length has to be run again
+        let longer = SweepOptions {
+            session: Some(SessionOptions {
+                trials: 5,
+                ..session
+            }),
+            ..options.clone()
+        };
+        let mut exceeded = ExceededLimits::default();
+        assert!(completed_runs(&runs, &longer, &results_dir, &mut exceeded).is_empty());
+
+        fs::remove_dir_all(results_dir).unwrap();
+    }
+
+    #[test]
+    fn test_manifest_rejects_unknown_fields() {
+        assert!(Manifest::parse("[[benchmark]]\nvm = [\"sp1\"]").is_err());
+    }
+}
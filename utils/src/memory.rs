@@ -1,10 +1,11 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use std::{fs, io};
 
-use nix::sys::wait::{waitpid, WaitStatus};
-use nix::unistd::{fork, ForkResult, Pid};
+use nix::sys::signal::{kill, killpg, Signal};
+use nix::sys::wait::{waitpid, WaitPidFlag, WaitStatus};
+use nix::unistd::{fork, setpgid, ForkResult, Pid};
 use rand::Rng;
 
 static MEMORY_TRACKING_LOCK: Mutex<()> = Mutex::new(());
@@ -13,20 +14,55 @@ static MEMORY_TRACKING_LOCK: Mutex<()> = Mutex::new(());
 // failure in the child before execv
 const EXEC_FAILURE_EXIT_CODE: i32 = 123;
 
-#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug)]
+// how often the child is polled when a timeout is set
+const TIMEOUT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// how long to wait for processes left in the cgroup to die
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum MemoryResult {
     Exited(i32),
     Signal(i32),
+    // killed after running for the given number of seconds
+    Timeout(u64),
 }
 
 impl MemoryResult {
     pub fn is_ok(self) -> bool {
         match self {
             MemoryResult::Exited(code) => code == 0,
-            MemoryResult::Signal(_) => false,
+            MemoryResult::Signal(_) | MemoryResult::Timeout(_) => false,
         }
     }
+
+    /// Whether the run was killed for running out of memory (SIGKILL) or time
+    pub fn exceeds_limits(self) -> bool {
+        matches!(self, MemoryResult::Signal(9) | MemoryResult::Timeout(_))
+    }
+}
+
+impl std::fmt::Display for MemoryResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MemoryResult::Exited(code) => write!(f, "exit code {}", code),
+            MemoryResult::Signal(9) => write!(f, "out of memory"),
+            MemoryResult::Signal(signal) => write!(f, "signal {}", signal),
+            MemoryResult::Timeout(seconds) => write!(f, "timeout after {} s", seconds),
+        }
+    }
+}
+
+/// Resource limits imposed on the child
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Limits {
+    /// Kill the child if it runs longer than this
+    pub timeout: Option<Duration>,
+    /// Memory limit of the cgroup in bytes (`memory.max`), the cgroup only
+    /// holds the child and what it spawns, not the runner, and the child
+    /// is killed with SIGKILL once it exceeds it
+    pub memory_max: Option<u64>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -35,13 +71,26 @@ pub struct MemoryUsage {
     pub result: MemoryResult,
 }
 
+/// Peak resident memory of the current process so far (`VmHWM`),
+/// `None` where `/proc` is not available
+pub fn peak_rss() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
 /// Run a command with memory monitoring and
 /// return the peak memory usage in bytes and exit status
+///
+/// If `current_dir` is given, the command is executed from that directory
 pub fn run_with_memory_tracking(
     allowed_exit_codes: &[i32],
     allowed_signals: &[i32],
     program: &str,
     args: &[String],
+    current_dir: Option<&Path>,
+    limits: &Limits,
 ) -> io::Result<MemoryUsage> {
     // Check if we're running as root
     if !nix::unistd::geteuid().is_root() {
@@ -82,13 +131,16 @@ pub fn run_with_memory_tracking(
     }
 
     if !cgroup_created {
-        return Err(io::Error::new(
-            io::ErrorKind::Other,
-            "Failed to create cgroup",
-        ));
+        return Err(io::Error::other("Failed to create cgroup"));
+    }
+
+    if let Some(memory_max) = limits.memory_max {
+        fs::write(cgroup_path.join("memory.max"), memory_max.to_string())?;
+        // don't let the child escape the limit by swapping
+        let _ = fs::write(cgroup_path.join("memory.swap.max"), "0");
     }
 
-    fn get_peak_memory(cgroup_path: &PathBuf) -> Result<u64, io::Error> {
+    fn get_peak_memory(cgroup_path: &Path) -> Result<u64, io::Error> {
         let memory_peak_path = cgroup_path.join("memory.peak");
         let content = fs::read_to_string(&memory_peak_path)?;
         let peak_memory = content.trim().parse::<u64>().map_err(|e| {
@@ -100,20 +152,26 @@ pub fn run_with_memory_tracking(
         Ok(peak_memory)
     }
 
-    // Add current process to the cgroup so children inherit it
+    // Only the child joins the cgroup, so neither the limit nor the
+    // measurement includes the memory of *THIS* program
     let procs_path = cgroup_path.join("cgroup.procs");
-    let pid: Pid = nix::unistd::getpid();
-    fs::write(&procs_path, pid.to_string())?;
-
-    // record memory usage before executing the child
-    // this assumes that memory usage of *THIS* program
-    // remains constant between starting the child and making the measurement
-    let mem_usage_before = get_peak_memory(&cgroup_path)?;
 
     // Fork and execute the command
     let result = match unsafe { fork() } {
         Ok(ForkResult::Child) => {
-            // In child process - become the program using exec
+            // In child process - move into the cgroup ("0" is the writing process)
+            // before exec so everything the program allocates is accounted
+            if fs::write(&procs_path, "0").is_err() {
+                std::process::exit(EXEC_FAILURE_EXIT_CODE);
+            }
+            // lead a new process group so a timeout
+            // can kill everything the program spawns, then exec
+            let _ = setpgid(Pid::from_raw(0), Pid::from_raw(0));
+            if let Some(dir) = current_dir {
+                if std::env::set_current_dir(dir).is_err() {
+                    std::process::exit(EXEC_FAILURE_EXIT_CODE);
+                }
+            }
             let program_cstring = match std::ffi::CString::new(program) {
                 Ok(s) => s,
                 Err(_) => std::process::exit(EXEC_FAILURE_EXIT_CODE),
@@ -131,46 +189,147 @@ pub fn run_with_memory_tracking(
             }
         }
         Ok(ForkResult::Parent { child }) => {
+            // set the group from both sides, otherwise a timeout
+            // could fire before the child got to it
+            let _ = setpgid(child, child);
             // In parent process - wait for child and get peak memory
-            match waitpid(child, None) {
-                Ok(WaitStatus::Exited(_, status)) => {
+            let status = wait_with_timeout(child, limits.timeout);
+            // processes that left the group are still in the cgroup
+            // and would skew the next measurement
+            drain_cgroup(&procs_path)?;
+            match status {
+                Ok(None) => Ok(MemoryResult::Timeout(
+                    limits.timeout.unwrap_or_default().as_secs(),
+                )),
+                Ok(Some(WaitStatus::Exited(_, status))) => {
                     if allowed_exit_codes.contains(&status) {
                         Ok(MemoryResult::Exited(status))
                     } else {
-                        Err(io::Error::new(
-                            io::ErrorKind::Other,
-                            format!("Unexpected exit code: {}", status),
-                        ))
+                        Err(io::Error::other(format!(
+                            "Unexpected exit code: {}",
+                            status
+                        )))
                     }
                 }
-                Ok(WaitStatus::Signaled(_, signal, _core_dump)) => {
+                Ok(Some(WaitStatus::Signaled(_, signal, _core_dump))) => {
                     if allowed_signals.contains(&(signal as i32)) {
                         Ok(MemoryResult::Signal(signal as i32))
                     } else {
-                        Err(io::Error::new(
-                            io::ErrorKind::Other,
-                            format!("program killed with: {} (Signal: {})", program, signal),
-                        ))
+                        Err(io::Error::other(format!(
+                            "program killed with: {} (Signal: {})",
+                            program, signal
+                        )))
                     }
                 }
-                _ => Err(io::Error::new(io::ErrorKind::Other, "Child process failed")),
+                _ => Err(io::Error::other("Child process failed")),
             }
         }
-        Err(e) => Err(io::Error::new(
-            io::ErrorKind::Other,
-            format!("Fork failed: {}", e),
-        )),
-    }?;
+        Err(e) => Err(io::Error::other(format!("Fork failed: {}", e))),
+    };
 
     // Child finished, read peak memory
-    let peak_memory_after = get_peak_memory(&cgroup_path)?;
+    let peak_memory = get_peak_memory(&cgroup_path);
 
-    // Remove the cgroup
-    let _ = fs::remove_dir(&cgroup_path);
+    // Remove the cgroup, it is empty after draining
+    if let Err(e) = fs::remove_dir(&cgroup_path) {
+        eprintln!("Failed to remove cgroup {}: {}", cgroup_path.display(), e);
+    }
 
-    //
+    let result = result?;
     Ok(MemoryUsage {
-        memory: peak_memory_after.saturating_sub(mem_usage_before),
+        memory: peak_memory?,
         result,
     })
 }
+
+/// Kill every process left in the cgroup and
+/// wait until they are gone from `cgroup.procs`
+fn drain_cgroup(procs_path: &Path) -> io::Result<()> {
+    let deadline = Instant::now() + DRAIN_TIMEOUT;
+    loop {
+        let remaining: Vec<Pid> = fs::read_to_string(procs_path)?
+            .lines()
+            .filter_map(|line| line.trim().parse().ok())
+            .map(Pid::from_raw)
+            .collect();
+        if remaining.is_empty() {
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            return Err(io::Error::other(format!(
+                "{} processes left in the cgroup after killing them",
+                remaining.len()
+            )));
+        }
+        for pid in remaining {
+            // the process may have exited in the meantime
+            let _ = kill(pid, Signal::SIGKILL);
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+/// Wait for the child to terminate, killing its process group once `timeout` has passed.
+///
+/// Returns `None` if the child was killed because of the timeout
+fn wait_with_timeout(child: Pid, timeout: Option<Duration>) -> nix::Result<Option<WaitStatus>> {
+    let Some(timeout) = timeout else {
+        return waitpid(child, None).map(Some);
+    };
+    let deadline = Instant::now() + timeout;
+    loop {
+        match waitpid(child, Some(WaitPidFlag::WNOHANG))? {
+            WaitStatus::StillAlive if Instant::now() >= deadline => {
+                killpg(child, Signal::SIGKILL)?;
+                waitpid(child, None)?;
+                return Ok(None);
+            }
+            WaitStatus::StillAlive => std::thread::sleep(TIMEOUT_POLL_INTERVAL),
+            status => return Ok(Some(status)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::os::unix::process::CommandExt;
+    use std::process::{Command, Stdio};
+
+    fn is_running(pid: Pid) -> bool {
+        // killed processes may linger as zombies until init reaps them
+        match fs::read_to_string(format!("/proc/{}/stat", pid)) {
+            Ok(stat) => !stat.rsplit(')').next().unwrap().trim().starts_with('Z'),
+            Err(_) => false,
+        }
+    }
+
+    #[test]
+    // the child is reaped by `wait_with_timeout`
+    #[allow(clippy::zombie_processes)]
+    fn test_timeout_kills_grandchildren() {
+        let mut child = Command::new("sh")
+            .args(["-c", "sleep 30 & echo $!; wait"])
+            .stdout(Stdio::piped())
+            .process_group(0)
+            .spawn()
+            .unwrap();
+        let mut line = String::new();
+        BufReader::new(child.stdout.take().unwrap())
+            .read_line(&mut line)
+            .unwrap();
+        let sleeper = Pid::from_raw(line.trim().parse().unwrap());
+        assert!(is_running(sleeper));
+
+        let child = Pid::from_raw(child.id() as i32);
+        let status = wait_with_timeout(child, Some(Duration::from_millis(200))).unwrap();
+        assert_eq!(status, None);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while is_running(sleeper) && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(!is_running(sleeper));
+    }
+}
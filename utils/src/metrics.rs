@@ -0,0 +1,236 @@
+use serde::Serialize;
+
+use crate::runner::format_bytes;
+use crate::{stats, BenchmarkConfigAndResult, BenchmarkStatus};
+
+/// What a single run of a workload computes, given its n
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Workload {
+    /// Bytes fed into the hash function
+    pub bytes_hashed: Option<u64>,
+    /// Elliptic curve point additions
+    pub ec_additions: Option<u64>,
+}
+
+impl Workload {
+    /// The workload of a program (without its variant), mirroring the inputs the drivers pass
+    pub fn new(workload: &str, n: u32) -> Workload {
+        let n = n as u64;
+        match workload {
+            // hash an input of n bytes
+            "sha2" | "sha3" | "blake" => Workload {
+                bytes_hashed: Some(n),
+                ..Default::default()
+            },
+            // hash a 32 byte digest n times
+            "sha2-chain" | "sha3-chain" | "blake-chain" => Workload {
+                bytes_hashed: Some(32 * n),
+                ..Default::default()
+            },
+            // add the generator n times
+            "ec" => Workload {
+                ec_additions: Some(n),
+                ..Default::default()
+            },
+            _ => Workload::default(),
+        }
+    }
+}
+
+fn ratio(numerator: Option<f64>, denominator: Option<f64>) -> Option<f64> {
+    Some(numerator? / denominator?)
+}
+
+/// Metrics normalizing the raw measurements of a successful run,
+/// so they can be compared across workloads and input sizes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Derived {
+    pub cycles_per_second: Option<f64>,
+    pub cycles_per_second_per_core: Option<f64>,
+    pub bytes_hashed_per_second: Option<f64>,
+    pub ec_additions_per_second: Option<f64>,
+    pub verifier_ms_per_proof_kb: Option<f64>,
+    pub memory_bytes_per_million_cycles: Option<f64>,
+}
+
+impl Derived {
+    /// Derived metrics of a result, all empty if the run failed
+    pub fn new(result: &BenchmarkConfigAndResult) -> Derived {
+        let BenchmarkStatus::Success(success) = &result.result else {
+            return Derived::default();
+        };
+        let id = result.id();
+        let workload = Workload::new(id.name.workload().0, id.n);
+        let secs = |durations: &[std::time::Duration]| {
+            let samples: Vec<f64> = durations.iter().map(|d| d.as_secs_f64()).collect();
+            stats::median(&samples).filter(|secs| *secs > 0.0)
+        };
+        let prover_secs = secs(&success.result.prover_durations);
+        let verifier_secs = secs(&success.result.verifier_durations);
+        let cycles = success.result.cycles().map(|cycles| cycles as f64);
+        let proof_kb = Some(success.result.proof_size as f64 / 1000.0).filter(|kb| *kb > 0.0);
+        let cores = result
+            .hygiene
+            .as_ref()
+            .and_then(|hygiene| hygiene.cpu_count);
+
+        let cycles_per_second = ratio(cycles, prover_secs);
+        Derived {
+            cycles_per_second,
+            cycles_per_second_per_core: ratio(cycles_per_second, cores.map(|c| c as f64)),
+            bytes_hashed_per_second: ratio(workload.bytes_hashed.map(|b| b as f64), prover_secs),
+            ec_additions_per_second: ratio(workload.ec_additions.map(|a| a as f64), prover_secs),
+            verifier_ms_per_proof_kb: ratio(verifier_secs.map(|secs| secs * 1000.0), proof_kb),
+            memory_bytes_per_million_cycles: ratio(
+                Some(success.peak_memory as f64),
+                cycles.map(|cycles| cycles / 1e6),
+            ),
+        }
+    }
+}
+
+/// The derived metrics, as shown in summaries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DerivedMetric {
+    CyclesPerSecond,
+    CyclesPerSecondPerCore,
+    BytesHashedPerSecond,
+    EcAdditionsPerSecond,
+    VerifierTimePerProofKb,
+    MemoryPerMillionCycles,
+}
+
+fn format_rate(value: f64, unit: &str) -> String {
+    if value >= 1e9 {
+        format!("{:.2} G{}/s", value / 1e9, unit)
+    } else if value >= 1e6 {
+        format!("{:.2} M{}/s", value / 1e6, unit)
+    } else if value >= 1e3 {
+        format!("{:.2} K{}/s", value / 1e3, unit)
+    } else {
+        format!("{:.2} {}/s", value, unit)
+    }
+}
+
+impl DerivedMetric {
+    pub const ALL: [DerivedMetric; 6] = [
+        DerivedMetric::CyclesPerSecond,
+        DerivedMetric::CyclesPerSecondPerCore,
+        DerivedMetric::BytesHashedPerSecond,
+        DerivedMetric::EcAdditionsPerSecond,
+        DerivedMetric::VerifierTimePerProofKb,
+        DerivedMetric::MemoryPerMillionCycles,
+    ];
+
+    pub fn value(&self, derived: &Derived) -> Option<f64> {
+        match self {
+            DerivedMetric::CyclesPerSecond => derived.cycles_per_second,
+            DerivedMetric::CyclesPerSecondPerCore => derived.cycles_per_second_per_core,
+            DerivedMetric::BytesHashedPerSecond => derived.bytes_hashed_per_second,
+            DerivedMetric::EcAdditionsPerSecond => derived.ec_additions_per_second,
+            DerivedMetric::VerifierTimePerProofKb => derived.verifier_ms_per_proof_kb,
+            DerivedMetric::MemoryPerMillionCycles => derived.memory_bytes_per_million_cycles,
+        }
+    }
+
+    /// Human readable value of the metric
+    pub fn format(&self, value: f64) -> String {
+        match self {
+            DerivedMetric::CyclesPerSecond | DerivedMetric::CyclesPerSecondPerCore => {
+                format_rate(value, "cycles")
+            }
+            DerivedMetric::BytesHashedPerSecond => format!("{}/s", format_bytes(value as u64)),
+            DerivedMetric::EcAdditionsPerSecond => format_rate(value, "adds"),
+            DerivedMetric::VerifierTimePerProofKb => format!("{:.3} ms/KB", value),
+            DerivedMetric::MemoryPerMillionCycles => {
+                format!("{}/Mcycle", format_bytes(value as u64))
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for DerivedMetric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DerivedMetric::CyclesPerSecond => write!(f, "proving throughput"),
+            DerivedMetric::CyclesPerSecondPerCore => write!(f, "proving throughput per core"),
+            DerivedMetric::BytesHashedPerSecond => write!(f, "bytes hashed per second"),
+            DerivedMetric::EcAdditionsPerSecond => write!(f, "EC additions per second"),
+            DerivedMetric::VerifierTimePerProofKb => write!(f, "verifier time per proof KB"),
+            DerivedMetric::MemoryPerMillionCycles => write!(f, "memory per million cycles"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::hygiene::HygieneReport;
+    use crate::memory::MemoryResult;
+    use crate::{BenchmarkConfig, BenchmarkResult, BenchmarkResultWithMemory, VM};
+
+    fn result(program: &str, n: u32, result: BenchmarkStatus) -> BenchmarkConfigAndResult {
+        BenchmarkConfigAndResult {
+            vm: VM::SP1,
+            config: BenchmarkConfig {
+                n,
+                program: program.to_string(),
+                verifier_iterations: 2,
+                ..Default::default()
+            },
+            result,
+            hygiene: Some(HygieneReport {
+                cpu_count: Some(8),
+                ..Default::default()
+            }),
+            provenance: None,
+            session: None,
+            sampling: None,
+        }
+    }
+
+    fn success() -> BenchmarkStatus {
+        BenchmarkStatus::Success(BenchmarkResultWithMemory {
+            result: BenchmarkResult {
+                proof_size: 2000,
+                prover_durations: vec![Duration::from_secs(4)],
+                verifier_durations: vec![Duration::from_millis(10), Duration::from_millis(30)],
+                cycle_count: 8_000_000,
+                ..Default::default()
+            },
+            peak_memory: 4_000_000_000,
+        })
+    }
+
+    #[test]
+    fn test_workload() {
+        assert_eq!(Workload::new("sha2", 1024).bytes_hashed, Some(1024));
+        assert_eq!(Workload::new("blake-chain", 10).bytes_hashed, Some(320));
+        assert_eq!(Workload::new("ec", 10).ec_additions, Some(10));
+        assert_eq!(Workload::new("mat-mul", 10), Workload::default());
+    }
+
+    #[test]
+    fn test_derived_metrics() {
+        let derived = Derived::new(&result("sha2-chain-precompile", 1000, success()));
+        assert_eq!(
+            derived,
+            Derived {
+                cycles_per_second: Some(2_000_000.0),
+                cycles_per_second_per_core: Some(250_000.0),
+                bytes_hashed_per_second: Some(8000.0),
+                ec_additions_per_second: None,
+                verifier_ms_per_proof_kb: Some(10.0),
+                memory_bytes_per_million_cycles: Some(500_000_000.0),
+            }
+        );
+
+        let failure = BenchmarkStatus::Failure(MemoryResult::Signal(9));
+        assert_eq!(
+            Derived::new(&result("sha2", 1000, failure)),
+            Derived::default()
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::metrics::Workload;
+use crate::{stats, BenchmarkConfigAndResult, BenchmarkStatus};
+
+/// Hardware prices to estimate the cost of a proof, e.g.
+///
+/// ```toml
+/// [[instance]]
+/// name = "r7i.8xlarge"
+/// dollars_per_hour = 2.1168
+/// cores = 32
+/// memory_gib = 256
+/// ```
+///
+/// The prover time is taken as measured, so price instances
+/// comparable to the machine the benchmarks ran on.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Pricing {
+    #[serde(rename = "instance", default)]
+    pub instances: Vec<Instance>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Instance {
+    pub name: String,
+    pub dollars_per_hour: f64,
+    pub cores: u32,
+    pub memory_gib: u64,
+}
+
+/// Estimated cost of a successful run on an instance
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cost {
+    pub dollars_per_proof: f64,
+    /// Cost of hashing a megabyte, for the hash workloads
+    pub dollars_per_mb_hashed: Option<f64>,
+    /// Whether the peak memory of the run fits into the memory of the instance
+    pub fits: bool,
+}
+
+impl Pricing {
+    pub fn parse(content: &str) -> Result<Pricing, String> {
+        toml::from_str(content).map_err(|e| format!("Invalid pricing: {}", e))
+    }
+
+    pub fn load(path: &Path) -> Result<Pricing, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Pricing::parse(&content)
+    }
+}
+
+impl Instance {
+    pub fn memory_bytes(&self) -> u64 {
+        self.memory_gib << 30
+    }
+
+    /// Cost of proving `result` on this instance, `None` if no proof was produced
+    pub fn cost(&self, result: &BenchmarkConfigAndResult) -> Option<Cost> {
+        let BenchmarkStatus::Success(success) = &result.result else {
+            return None;
+        };
+        let samples: Vec<f64> = success
+            .result
+            .prover_durations
+            .iter()
+            .map(|d| d.as_secs_f64())
+            .collect();
+        let prover_secs = stats::median(&samples)?;
+        let dollars_per_proof = prover_secs / 3600.0 * self.dollars_per_hour;
+
+        let id = result.id();
+        let workload = Workload::new(id.name.workload().0, id.n);
+        Some(Cost {
+            dollars_per_proof,
+            dollars_per_mb_hashed: workload
+                .bytes_hashed
+                .filter(|bytes| *bytes > 0)
+                .map(|bytes| dollars_per_proof / (bytes as f64 / 1e6)),
+            fits: success.peak_memory <= self.memory_bytes(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::runner;
+    use crate::{BenchmarkConfig, BenchmarkResult, BenchmarkResultWithMemory, VM};
+
+    #[test]
+    fn test_cost_per_proof() {
+        let pricing = Pricing::parse(
+            r#"
+            [[instance]]
+            name = "small"
+            dollars_per_hour = 1.8
+            cores = 16
+            memory_gib = 32
+
+            [[instance]]
+            name = "large"
+            dollars_per_hour = 7.2
+            cores = 64
+            memory_gib = 512
+            "#,
+        )
+        .unwrap();
+        let result = BenchmarkConfigAndResult {
+            vm: VM::SP1,
+            config: BenchmarkConfig {
+                n: 2_000_000,
+                program: "sha2-precompile".to_string(),
+                verifier_iterations: 1,
+                ..Default::default()
+            },
+            result: BenchmarkStatus::Success(BenchmarkResultWithMemory {
+                result: BenchmarkResult {
+                    prover_durations: vec![Duration::from_secs(100), Duration::from_secs(200)],
+                    ..Default::default()
+                },
+                peak_memory: 64 << 30,
+            }),
+            hygiene: None,
+            provenance: None,
+            session: None,
+            sampling: None,
+        };
+
+        let small = pricing.instances[0].cost(&result).unwrap();
+        assert!((small.dollars_per_proof - 0.075).abs() < 1e-9);
+        assert!((small.dollars_per_mb_hashed.unwrap() - 0.0375).abs() < 1e-9);
+        assert!(!small.fits);
+        let large = pricing.instances[1].cost(&result).unwrap();
+        assert!((large.dollars_per_proof - 0.3).abs() < 1e-9);
+        assert!(large.fits);
+    }
+
+    #[test]
+    fn test_example_pricing_parses() {
+        let path = runner::repository_root().join("pricing.toml");
+        let pricing = Pricing::load(&path).unwrap();
+        assert!(!pricing.instances.is_empty());
+    }
+}
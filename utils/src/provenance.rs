@@ -0,0 +1,82 @@
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// Where and with what a benchmark result was produced
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Provenance {
+    pub hostname: Option<String>,
+    // commit of this repository, if it could be determined
+    pub git_commit: Option<String>,
+    pub utils_version: String,
+    // canonical path of the driver executable
+    pub driver: String,
+    // fingerprint of the driver build
+    pub driver_size_bytes: Option<u64>,
+    pub driver_modified_unix: Option<u64>,
+    // full command line the driver was invoked with
+    pub driver_args: Vec<String>,
+    pub started_at_unix: u64,
+}
+
+impl Provenance {
+    pub fn collect(root: &Path, driver: &Path, driver_args: &[String]) -> Provenance {
+        let metadata = fs::metadata(driver).ok();
+        Provenance {
+            hostname: hostname(),
+            git_commit: git_commit(root),
+            utils_version: env!("CARGO_PKG_VERSION").to_string(),
+            driver: driver
+                .canonicalize()
+                .unwrap_or_else(|_| driver.to_path_buf())
+                .display()
+                .to_string(),
+            driver_size_bytes: metadata.as_ref().map(|m| m.len()),
+            driver_modified_unix: metadata
+                .and_then(|m| m.modified().ok())
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs()),
+            driver_args: driver_args.to_vec(),
+            started_at_unix: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        }
+    }
+
+    /// Whether both results were produced by the same driver build on the same machine
+    pub fn same_origin(&self, other: &Provenance) -> bool {
+        self.hostname == other.hostname
+            && self.driver == other.driver
+            && self.driver_size_bytes == other.driver_size_bytes
+            && self.driver_modified_unix == other.driver_modified_unix
+    }
+}
+
+fn hostname() -> Option<String> {
+    nix::unistd::gethostname()
+        .ok()
+        .and_then(|name| name.into_string().ok())
+}
+
+/// Resolve HEAD by reading the git directory directly, since the runner
+/// usually runs as root and git refuses to operate on repositories of other users
+fn git_commit(root: &Path) -> Option<String> {
+    let git_dir = root.join(".git");
+    let head = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    let head = head.trim();
+    let Some(reference) = head.strip_prefix("ref: ") else {
+        // detached HEAD
+        return Some(head.to_string());
+    };
+    if let Ok(commit) = fs::read_to_string(git_dir.join(reference)) {
+        return Some(commit.trim().to_string());
+    }
+    let packed_refs = fs::read_to_string(git_dir.join("packed-refs")).ok()?;
+    packed_refs.lines().find_map(|line| {
+        let (commit, name) = line.split_once(' ')?;
+        (name == reference).then(|| commit.to_string())
+    })
+}
@@ -0,0 +1,64 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::BenchmarkConfigAndResult;
+
+/// Load every result saved in `dir`, ordered by benchmark id.
+///
+/// Files that are not a benchmark result are reported and ignored.
+pub fn load(dir: &Path) -> io::Result<Vec<BenchmarkConfigAndResult>> {
+    let mut results = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|extension| extension != "json") {
+            continue;
+        }
+        let content = fs::read_to_string(&path)?;
+        match serde_json::from_str::<BenchmarkConfigAndResult>(&content) {
+            Ok(result) => results.push(result),
+            Err(e) => eprintln!("Ignoring {}: {}", path.display(), e),
+        }
+    }
+    results.sort_by_key(|result| result.id().to_string());
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MemoryResult;
+    use crate::{BenchmarkConfig, BenchmarkStatus, VM};
+
+    #[test]
+    fn test_load_results() {
+        let dir = std::env::temp_dir().join(format!("utils-load-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        for n in [8192, 4096] {
+            let result = BenchmarkConfigAndResult {
+                vm: VM::SP1,
+                config: BenchmarkConfig {
+                    n,
+                    program: "fib".to_string(),
+                    verifier_iterations: 1,
+                    ..Default::default()
+                },
+                result: BenchmarkStatus::Failure(MemoryResult::Signal(9)),
+                hygiene: None,
+                provenance: None,
+                session: None,
+                sampling: None,
+            };
+            fs::write(dir.join(format!("{}.json", result.id())), result.to_json()).unwrap();
+        }
+        fs::write(dir.join("notes.json"), "{}").unwrap();
+        fs::write(dir.join("README.md"), "# results").unwrap();
+
+        let results = load(&dir).unwrap();
+        let ids: Vec<String> = results.iter().map(|r| r.id().to_string()).collect();
+        assert_eq!(ids, vec!["sp1-fib-n4096", "sp1-fib-n8192"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,547 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::memory::{Limits, MemoryResult};
+use crate::provenance::Provenance;
+use crate::sampling::SamplingOptions;
+use crate::{
+    hygiene, memory, BenchmarkConfig, BenchmarkConfigAndResult, BenchmarkId, BenchmarkResult,
+    BenchmarkResultWithMemory, BenchmarkStatus, Sampling, SkippedAfterFailure,
+};
+
+/// Everything needed to run a single benchmark driver
+#[derive(Debug, Clone)]
+pub struct RunRequest {
+    pub id: BenchmarkId,
+    /// Driver executable, relative to `working_dir` (if given)
+    pub bin: String,
+    /// Directory the driver runs in, defaults to the current directory
+    pub working_dir: Option<PathBuf>,
+    pub verifier_iterations: u32,
+    pub allowed_exit_codes: Vec<i32>,
+    pub allowed_signals: Vec<i32>,
+    pub limits: Limits,
+    /// Refuse to run the benchmark if the machine is noisy
+    pub strict: bool,
+    /// Run the driver again while the samples are noisy
+    pub sampling: Option<SamplingOptions>,
+    /// Extra arguments passed to the driver (e.g. `--program fib`)
+    pub args: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum RunError {
+    /// The machine was too noisy to run the benchmark in strict mode
+    NoisyMachine(Vec<String>),
+    Io(io::Error),
+}
+
+impl From<io::Error> for RunError {
+    fn from(e: io::Error) -> Self {
+        RunError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for RunError {
+    fn from(e: serde_json::Error) -> Self {
+        RunError::Io(e.into())
+    }
+}
+
+impl std::fmt::Display for RunError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RunError::NoisyMachine(warnings) => {
+                write!(f, "machine is too noisy: {}", warnings.join(", "))
+            }
+            RunError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Directory holding all benchmark results of this repository
+pub fn results_dir() -> PathBuf {
+    repository_root().join("benchmark_results")
+}
+
+pub fn repository_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .to_path_buf()
+}
+
+/// Where the result of a benchmark is saved, path separators in
+/// backend option values must not lead out of `results_dir`
+pub fn result_path(results_dir: &Path, id: &BenchmarkId) -> PathBuf {
+    let name = id.to_string().replace(['/', '\\'], "_");
+    results_dir.join(format!("{}.json", name))
+}
+
+/// How existing results are treated when resuming a sweep
+#[derive(Debug, Clone, Default)]
+pub struct ResumePolicy {
+    /// Run benchmarks again whose existing result is a failure
+    pub retry_failed: bool,
+    /// Benchmarks (as in `sp1-fib-n4096`) that are always run again
+    pub force: Vec<String>,
+}
+
+impl ResumePolicy {
+    /// The existing result that makes running `request` again unnecessary, if any.
+    ///
+    /// A result only counts if it was produced with the same config,
+    /// by the same driver build on the same machine. In a session of
+    /// `trials`, it also has to hold all of them.
+    pub fn completed(
+        &self,
+        request: &RunRequest,
+        results_dir: &Path,
+        trials: Option<u32>,
+    ) -> Option<BenchmarkConfigAndResult> {
+        if self.force.contains(&request.id.to_string()) {
+            return None;
+        }
+        let content = fs::read_to_string(result_path(results_dir, &request.id)).ok()?;
+        let existing: BenchmarkConfigAndResult = serde_json::from_str(&content).ok()?;
+        if existing.config != request.config() {
+            return None;
+        }
+        let current = request.provenance(&[]);
+        if !existing.provenance.as_ref()?.same_origin(&current) {
+            return None;
+        }
+        if let Some(trials) = trials {
+            if existing.session.as_ref()?.trials.len() != trials as usize {
+                return None;
+            }
+        }
+        match existing.result {
+            BenchmarkStatus::Success(_) => Some(existing),
+            BenchmarkStatus::Failure(_) | BenchmarkStatus::Skipped(_) if !self.retry_failed => {
+                Some(existing)
+            }
+            BenchmarkStatus::Failure(_) | BenchmarkStatus::Skipped(_) => None,
+        }
+    }
+}
+
+impl RunRequest {
+    pub fn config(&self) -> BenchmarkConfig {
+        BenchmarkConfig {
+            n: self.id.n,
+            program: self.id.name.program.clone(),
+            verifier_iterations: self.verifier_iterations,
+            backend_options: self.id.backend_options.clone(),
+            mode: self.id.mode,
+        }
+    }
+
+    pub(crate) fn provenance(&self, driver_args: &[String]) -> Provenance {
+        let driver = self.working_dir.clone().unwrap_or_default().join(&self.bin);
+        Provenance::collect(&repository_root(), &driver, driver_args)
+    }
+}
+
+/// Backend options are passed to the driver as `--<key> <value>`
+pub fn backend_args(id: &BenchmarkId) -> Vec<String> {
+    id.backend_options
+        .iter()
+        .flat_map(|(key, value)| [format!("--{}", key), value.clone()])
+        .collect()
+}
+
+/// Run the benchmark driver in a separate cgroup,
+/// save the result to `results_dir` and return it
+pub fn run(request: &RunRequest, results_dir: &Path) -> Result<BenchmarkConfigAndResult, RunError> {
+    let ident = &request.id;
+
+    // Create benchmark_results directory if it doesn't exist
+    fs::create_dir_all(results_dir)?;
+
+    // Prepare benchmark arguments
+    let mut benchmark_args = vec![
+        "--n".to_string(),
+        ident.n.to_string(),
+        "--verifier-iterations".to_string(),
+        request.verifier_iterations.to_string(),
+    ];
+    benchmark_args.extend(request.args.iter().cloned());
+    if !ident.mode.is_prove() {
+        benchmark_args.extend(["--mode".to_string(), ident.mode.to_string()]);
+    }
+    benchmark_args.extend(backend_args(ident));
+
+    // Inspect the machine for sources of noise before launching the child
+    let hygiene = hygiene::check();
+    for warning in &hygiene.warnings {
+        eprintln!("Hygiene warning: {}", warning);
+    }
+    if request.strict && !hygiene.is_clean() {
+        return Err(RunError::NoisyMachine(hygiene.warnings));
+    }
+
+    let provenance = request.provenance(&benchmark_args);
+
+    let config = request.config();
+    let mut sampling = request.sampling.map(|_| Sampling {
+        runs: 0,
+        converged: false,
+    });
+    let mut outcome = run_driver(request, &benchmark_args)?;
+
+    // take more samples while they are noisy, a failing or broken run keeps the samples so far
+    if let (Some(options), Some(sampling), Ok(merged)) =
+        (&request.sampling, &mut sampling, &mut outcome)
+    {
+        sampling.runs = 1;
+        sampling.converged = options.result_converged(merged);
+        while options.run_again(sampling.runs, sampling.converged) {
+            let reason = if sampling.converged {
+                "fewer runs than --min-runs"
+            } else {
+                "samples are noisy"
+            };
+            println!(
+                "Running {} again, {} ({}/{})",
+                ident,
+                reason,
+                sampling.runs + 1,
+                options.max_runs
+            );
+            match run_driver(request, &benchmark_args) {
+                Ok(Ok(result)) => merged.add_samples(&result),
+                Ok(Err(failure)) => {
+                    eprintln!("Re-sampling failed with: {:?}", failure);
+                    break;
+                }
+                Err(e) => {
+                    eprintln!("Re-sampling could not be run: {}", e);
+                    break;
+                }
+            }
+            sampling.runs += 1;
+            sampling.converged = options.result_converged(merged);
+        }
+        if !sampling.converged {
+            eprintln!(
+                "Samples of {} did not converge after {} runs",
+                ident, sampling.runs
+            );
+        }
+    }
+
+    let result = match outcome {
+        Ok(result) => {
+            print_overview(ident, &result);
+
+            // return successful benchmark result
+            BenchmarkConfigAndResult {
+                vm: ident.name.vm,
+                config,
+                result: BenchmarkStatus::Success(result),
+                hygiene: Some(hygiene),
+                provenance: Some(provenance),
+                session: None,
+                sampling,
+            }
+        }
+        Err(failure) => {
+            // print error message
+            eprintln!("Benchmark failed with: {:?}", failure);
+            BenchmarkConfigAndResult {
+                vm: ident.name.vm,
+                config,
+                result: BenchmarkStatus::Failure(failure),
+                hygiene: Some(hygiene),
+                provenance: Some(provenance),
+                session: None,
+                sampling: None,
+            }
+        }
+    };
+
+    // Save JSON to permanent location
+    let json_path = save(results_dir, &result)?;
+    println!("Results saved to: {}", json_path.display());
+
+    Ok(result)
+}
+
+/// Run the benchmark binary once in a seperate cgroup and read the result it leaves behind
+fn run_driver(
+    request: &RunRequest,
+    benchmark_args: &[String],
+) -> Result<Result<BenchmarkResultWithMemory, MemoryResult>, RunError> {
+    let mem_usage = memory::run_with_memory_tracking(
+        &request.allowed_exit_codes,
+        &request.allowed_signals,
+        &request.bin,
+        benchmark_args,
+        request.working_dir.as_deref(),
+        &request.limits,
+    )?;
+
+    // the driver leaves its result in its working directory
+    let temporary_results = request
+        .working_dir
+        .clone()
+        .unwrap_or_default()
+        .join("results.json");
+    if !mem_usage.result.is_ok() {
+        let _ = fs::remove_file(&temporary_results);
+        return Ok(Err(mem_usage.result));
+    }
+    let file_contents = fs::read_to_string(&temporary_results)?;
+    let _ = fs::remove_file(&temporary_results);
+
+    Ok(Ok(BenchmarkResultWithMemory {
+        result: serde_json::from_str::<BenchmarkResult>(&file_contents)?,
+        peak_memory: mem_usage.memory,
+    }))
+}
+
+/// Record that the benchmark was not run, since a smaller n already exceeded the limits
+pub fn skip(
+    request: &RunRequest,
+    results_dir: &Path,
+    skipped: SkippedAfterFailure,
+) -> io::Result<BenchmarkConfigAndResult> {
+    fs::create_dir_all(results_dir)?;
+    let result = BenchmarkConfigAndResult {
+        vm: request.id.name.vm,
+        config: request.config(),
+        result: BenchmarkStatus::Skipped(skipped),
+        hygiene: None,
+        provenance: Some(request.provenance(&[])),
+        session: None,
+        sampling: None,
+    };
+    save(results_dir, &result)?;
+    Ok(result)
+}
+
+/// Save the result to `results_dir` and return its path
+pub fn save(results_dir: &Path, result: &BenchmarkConfigAndResult) -> io::Result<PathBuf> {
+    let path = result_path(results_dir, &result.id());
+    fs::write(&path, result.to_json())?;
+    Ok(path)
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    const KB: u64 = 1000;
+    const MB: u64 = KB * 1000;
+    const GB: u64 = MB * 1000;
+    if bytes >= GB {
+        format!("{:.2} GB", bytes as f64 / GB as f64)
+    } else if bytes >= MB {
+        format!("{:.2} MB", bytes as f64 / MB as f64)
+    } else if bytes >= KB {
+        format!("{:.2} KB", bytes as f64 / KB as f64)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
+// print an overview for debugging
+fn print_overview(ident: &BenchmarkId, result: &BenchmarkResultWithMemory) {
+    let prover_times: String = result
+        .result
+        .prover_durations
+        .iter()
+        .map(|d| d.as_secs().to_string())
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    let verifier_times: String = result
+        .result
+        .verifier_durations
+        .iter()
+        .map(|d| d.as_millis().to_string())
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    println!("Results of {}", ident);
+    // drivers may report the execution when proving as well
+    let execution = result.result.execution.as_ref();
+    if let Some(execution) = execution.filter(|_| !ident.mode.is_prove()) {
+        println!("  Peak Memory   : {}", format_bytes(result.peak_memory));
+        print_cycles(result);
+        if let Some(trace_length) = execution.trace_length {
+            println!("  Trace Length  : {}", trace_length);
+        }
+        if let Some(segments) = execution.segments {
+            println!("  Segments      : {}", segments);
+        }
+        if let Some(touched) = execution.touched_memory_addresses {
+            println!("  Touched Memory: {} addresses", touched);
+        }
+        if let Some(gas) = execution.gas {
+            println!("  Gas           : {}", gas);
+        }
+        if !execution.syscall_counts.is_empty() {
+            let syscalls: Vec<String> = execution
+                .syscall_counts
+                .iter()
+                .map(|(name, count)| format!("{} {}", name, count))
+                .collect();
+            println!("  Syscalls      : {}", syscalls.join(", "));
+        }
+        println!(
+            "  Execution Time: {:.3} ms",
+            execution.duration.as_secs_f64() * 1000.0
+        );
+        return;
+    }
+    println!(
+        "  Proof Size    : {}",
+        format_bytes(result.result.proof_size as u64)
+    );
+    println!("  Peak Memory   : {}", format_bytes(result.peak_memory));
+    print_cycles(result);
+    println!("  Prover Time   : {} sec", prover_times);
+    println!("  Verifier Time : {} ms", verifier_times);
+    if let Some(preprocessing) = &result.result.preprocessing {
+        println!(
+            "  Preprocessing : compile {:.1} s, prover {:.1} s ({}), verifier {:.1} s ({}){}",
+            preprocessing.compile_duration.as_secs_f64(),
+            preprocessing.prover_duration.as_secs_f64(),
+            format_bytes(preprocessing.prover_size as u64),
+            preprocessing.verifier_duration.as_secs_f64(),
+            format_bytes(preprocessing.verifier_size as u64),
+            if preprocessing.reused { ", reused" } else { "" }
+        );
+    }
+    if let Some(wrap) = &result.result.wrap {
+        println!(
+            "  Wrap          : {:.1} s, from a {} compressed proof",
+            wrap.duration.as_secs_f64(),
+            format_bytes(wrap.compressed_proof_size as u64)
+        );
+    }
+    if let Some(segments) = execution.and_then(|execution| execution.segments) {
+        println!("  Segments      : {}", segments);
+    }
+}
+
+fn print_cycles(result: &BenchmarkResultWithMemory) {
+    match result.result.cycles() {
+        Some(cycles) => println!("  Cycles Count  : {}", cycles),
+        None => println!("  Cycles Count  : unavailable"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MemoryResult;
+    use crate::{BenchmarkName, VM};
+
+    fn request() -> RunRequest {
+        RunRequest {
+            id: BenchmarkId {
+                name: BenchmarkName {
+                    vm: VM::SP1,
+                    program: "fib".to_string(),
+                },
+                n: 4096,
+                backend_options: Default::default(),
+                mode: Default::default(),
+            },
+            bin: "target/release/sp1-script".to_string(),
+            working_dir: None,
+            verifier_iterations: 20,
+            allowed_exit_codes: vec![0],
+            allowed_signals: vec![9],
+            limits: Limits::default(),
+            strict: false,
+            sampling: None,
+            args: vec![],
+        }
+    }
+
+    fn save(results_dir: &Path, request: &RunRequest, result: BenchmarkStatus) {
+        let result = BenchmarkConfigAndResult {
+            vm: request.id.name.vm,
+            config: request.config(),
+            result,
+            hygiene: None,
+            provenance: Some(request.provenance(&[])),
+            session: None,
+            sampling: None,
+        };
+        fs::create_dir_all(results_dir).unwrap();
+        fs::write(result_path(results_dir, &request.id), result.to_json()).unwrap();
+    }
+
+    #[test]
+    fn test_result_path_stays_in_results_dir() {
+        let results_dir = std::env::temp_dir().join(format!("result-path-{}", std::process::id()));
+        let mut request = request();
+        request
+            .id
+            .backend_options
+            .insert("cache".to_string(), "/tmp/cache/../n256".to_string());
+
+        let path = result_path(&results_dir, &request.id);
+        assert_eq!(path.parent(), Some(results_dir.as_path()));
+        assert_eq!(
+            path.file_name().unwrap(),
+            "sp1-fib-cache=_tmp_cache_.._n256-n4096.json"
+        );
+
+        save(
+            &results_dir,
+            &request,
+            BenchmarkStatus::Success(Default::default()),
+        );
+        assert!(path.exists());
+
+        fs::remove_dir_all(results_dir).unwrap();
+    }
+
+    #[test]
+    fn test_resume_skips_completed_results() {
+        let results_dir = std::env::temp_dir().join(format!("resume-{}", std::process::id()));
+        let request = request();
+        let resume = ResumePolicy::default();
+
+        // nothing has been run yet
+        assert!(resume.completed(&request, &results_dir, None).is_none());
+
+        save(
+            &results_dir,
+            &request,
+            BenchmarkStatus::Success(Default::default()),
+        );
+        assert!(resume.completed(&request, &results_dir, None).is_some());
+
+        // a different config has to be run again
+        let mut other = request.clone();
+        other.verifier_iterations = 1;
+        assert!(resume.completed(&other, &results_dir, None).is_none());
+
+        // forced benchmarks are always run again
+        let forced = ResumePolicy {
+            force: vec!["sp1-fib-n4096".to_string()],
+            ..Default::default()
+        };
+        assert!(forced.completed(&request, &results_dir, None).is_none());
+
+        // failures are only run again on request
+        save(
+            &results_dir,
+            &request,
+            BenchmarkStatus::Failure(MemoryResult::Signal(9)),
+        );
+        assert!(resume.completed(&request, &results_dir, None).is_some());
+        let retry = ResumePolicy {
+            retry_failed: true,
+            ..Default::default()
+        };
+        assert!(retry.completed(&request, &results_dir, None).is_none());
+
+        fs::remove_dir_all(results_dir).unwrap();
+    }
+}
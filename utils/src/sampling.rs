@@ -0,0 +1,114 @@
+use crate::compare::Metric;
+use crate::{stats, BenchmarkResultWithMemory};
+
+/// Metrics whose samples are checked for noise
+const METRICS: [Metric; 2] = [Metric::ProverTime, Metric::VerifierTime];
+
+/// Run the driver of a benchmark again while its prover or verifier
+/// samples are noisy, until the mean is known precisely enough
+#[derive(Debug, Clone, Copy)]
+pub struct SamplingOptions {
+    /// Samples whose coefficient of variation exceeds this are noisy (0.05 = 5%)
+    pub max_cv: f64,
+    /// Noisy samples are taken until the 95% confidence interval
+    /// of their mean is this narrow, relative to the mean (0.05 = ±5%)
+    pub target_ci: f64,
+    /// Always run the driver at least this often for one benchmark
+    pub min_runs: u32,
+    /// Never run the driver more often than this for one benchmark
+    pub max_runs: u32,
+}
+
+impl SamplingOptions {
+    /// Whether the samples are precise enough. A single sample shows no
+    /// noise, more of them are only taken to reach `min_runs`.
+    pub fn converged(&self, samples: &[f64]) -> bool {
+        let (Some(cv), Some(ci)) = (
+            stats::coefficient_of_variation(samples),
+            stats::confidence_interval(samples),
+        ) else {
+            return true;
+        };
+        cv <= self.max_cv || ci <= self.target_ci
+    }
+
+    /// Whether the driver has to run again after `runs` runs
+    pub fn run_again(&self, runs: u32, converged: bool) -> bool {
+        runs < self.max_runs && (runs < self.min_runs || !converged)
+    }
+
+    /// Whether the prover and verifier samples of a run are precise enough
+    pub fn result_converged(&self, result: &BenchmarkResultWithMemory) -> bool {
+        METRICS
+            .into_iter()
+            .all(|metric| self.converged(&metric.samples(result)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::BenchmarkResult;
+
+    #[test]
+    fn test_sampling_convergence() {
+        let options = SamplingOptions {
+            max_cv: 0.05,
+            target_ci: 0.05,
+            min_runs: 1,
+            max_runs: 10,
+        };
+
+        // nothing measured, nothing to re-sample
+        assert!(options.converged(&[]));
+        assert!(options.converged(&[10.0]));
+        // stable samples
+        assert!(options.converged(&[10.0, 10.1, 9.9]));
+        // noisy samples, until there are enough of them
+        let noisy = [10.0, 12.0, 8.0, 11.0, 9.0];
+        assert!(!options.converged(&noisy));
+        let many: Vec<f64> = noisy.iter().cycle().take(60).copied().collect();
+        assert!(options.converged(&many));
+
+        let result = |prover_secs: &[u64], verifier_ms: &[u64]| BenchmarkResultWithMemory {
+            result: BenchmarkResult {
+                prover_durations: prover_secs
+                    .iter()
+                    .map(|s| Duration::from_secs(*s))
+                    .collect(),
+                verifier_durations: verifier_ms
+                    .iter()
+                    .map(|ms| Duration::from_millis(*ms))
+                    .collect(),
+                ..Default::default()
+            },
+            peak_memory: 0,
+        };
+        assert!(options.result_converged(&result(&[10], &[20, 20, 20])));
+        assert!(options.result_converged(&result(&[10, 10], &[20, 20, 20])));
+        assert!(!options.result_converged(&result(&[10, 10], &[20, 30, 10])));
+    }
+
+    #[test]
+    fn test_sampling_runs() {
+        let options = SamplingOptions {
+            max_cv: 0.05,
+            target_ci: 0.05,
+            min_runs: 1,
+            max_runs: 3,
+        };
+        // a run without noise is not repeated
+        assert!(!options.run_again(1, true));
+        assert!(options.run_again(1, false));
+        assert!(!options.run_again(3, false));
+
+        let options = SamplingOptions {
+            min_runs: 2,
+            ..options
+        };
+        assert!(options.run_again(1, true));
+        assert!(!options.run_again(2, true));
+    }
+}
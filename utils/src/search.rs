@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+
+use crate::matrix::{MatrixRun, SweepOptions};
+use crate::runner::{self, RunError};
+use crate::{BenchmarkId, BenchmarkName, BenchmarkStatus, Mode};
+
+/// Search for the largest n of a benchmark that still proves
+/// within the memory and time limits of the sweep options
+#[derive(Debug, Clone)]
+pub struct Search {
+    pub name: BenchmarkName,
+    pub backend_options: BTreeMap<String, String>,
+    pub mode: Mode,
+    pub verifier_iterations: u32,
+    /// First n to probe
+    pub start_n: u32,
+    /// Never probe beyond this n
+    pub max_n: Option<u32>,
+    /// Stop once the largest proven and the smallest failed n are this close
+    pub resolution: u32,
+}
+
+/// Outcome of a search
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Bounds {
+    /// Largest n that proved successfully
+    pub proven: Option<u32>,
+    /// Smallest n that failed
+    pub failed: Option<u32>,
+}
+
+/// Find the largest n for which `proves` succeeds, assuming that
+/// it keeps failing once it failed for some n.
+///
+/// n is doubled starting from `start` (at least 1) until a probe fails,
+/// then the gap is narrowed by binary search down to `resolution`.
+pub fn largest_n<E>(
+    start: u32,
+    max: Option<u32>,
+    resolution: u32,
+    mut proves: impl FnMut(u32) -> Result<bool, E>,
+) -> Result<Bounds, E> {
+    // doubling 0 would never get anywhere
+    let max = max.unwrap_or(u32::MAX).max(1);
+    let mut bounds = Bounds::default();
+
+    // exponential growth
+    let mut n = start.max(1).min(max);
+    loop {
+        if !proves(n)? {
+            bounds.failed = Some(n);
+            break;
+        }
+        bounds.proven = Some(n);
+        let next = n.saturating_mul(2).min(max);
+        if next == n {
+            return Ok(bounds);
+        }
+        n = next;
+    }
+
+    // binary search between the largest proven and the smallest failed n
+    let (Some(mut proven), Some(mut failed)) = (bounds.proven, bounds.failed) else {
+        return Ok(bounds);
+    };
+    while failed - proven > resolution.max(1) {
+        let mid = proven + (failed - proven) / 2;
+        if proves(mid)? {
+            proven = mid;
+        } else {
+            failed = mid;
+        }
+    }
+    Ok(Bounds {
+        proven: Some(proven),
+        failed: Some(failed),
+    })
+}
+
+impl Search {
+    fn run(&self, n: u32) -> MatrixRun {
+        MatrixRun {
+            id: BenchmarkId {
+                name: self.name.clone(),
+                n,
+                backend_options: self.backend_options.clone(),
+                mode: self.mode,
+            },
+            verifier_iterations: self.verifier_iterations,
+        }
+    }
+}
+
+/// Run the search, every probe is saved as a normal benchmark result
+pub fn run(search: &Search, options: &SweepOptions) -> Result<Bounds, RunError> {
+    let root = runner::repository_root();
+    let results_dir = runner::results_dir();
+
+    let mut probes = 0;
+    let bounds = largest_n::<RunError>(search.start_n, search.max_n, search.resolution, |n| {
+        probes += 1;
+        let run = search.run(n);
+        let request = run.request(&root, options);
+        if let Some(resume) = &options.resume {
+            if let Some(existing) = resume.completed(&request, &results_dir, None) {
+                println!("[probe {}] Reusing {}", probes, run.id);
+                return Ok(matches!(existing.result, BenchmarkStatus::Success(_)));
+            }
+        }
+        println!("[probe {}] Running {}", probes, run.id);
+        let result = runner::run(&request, &results_dir)?;
+        Ok(matches!(result.result, BenchmarkStatus::Success(_)))
+    })?;
+
+    match bounds {
+        Bounds {
+            proven: Some(proven),
+            failed: Some(failed),
+        } => println!(
+            "Largest n of {} within the limits: {} ({} failed)",
+            search.name, proven, failed
+        ),
+        Bounds {
+            proven: Some(proven),
+            failed: None,
+        } => println!(
+            "Largest n of {} within the limits: {} (maximum n reached)",
+            search.name, proven
+        ),
+        Bounds { proven: None, .. } => println!(
+            "{} exceeds the limits already at n = {}",
+            search.name, search.start_n
+        ),
+    }
+    Ok(bounds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search(limit: u32, start: u32, max: Option<u32>, resolution: u32) -> (Bounds, Vec<u32>) {
+        let mut probes = Vec::new();
+        let bounds = largest_n::<()>(start, max, resolution, |n| {
+            probes.push(n);
+            Ok(n <= limit)
+        })
+        .unwrap();
+        (bounds, probes)
+    }
+
+    #[test]
+    fn test_largest_n() {
+        let (bounds, probes) = search(100, 16, None, 1);
+        assert_eq!(bounds.proven, Some(100));
+        assert_eq!(bounds.failed, Some(101));
+        assert_eq!(&probes[..4], &[16, 32, 64, 128]);
+
+        // a coarser resolution needs fewer probes
+        let (bounds, coarse) = search(100, 16, None, 16);
+        assert_eq!(bounds.proven, Some(96));
+        assert_eq!(bounds.failed, Some(112));
+        assert!(coarse.len() < probes.len());
+
+        // already failing at the start
+        let (bounds, probes) = search(100, 128, None, 1);
+        assert_eq!(bounds.proven, None);
+        assert_eq!(probes, vec![128]);
+
+        // never failing up to the maximum
+        let (bounds, probes) = search(100, 16, Some(50), 1);
+        assert_eq!(bounds.proven, Some(50));
+        assert_eq!(bounds.failed, None);
+        assert_eq!(probes, vec![16, 32, 50]);
+
+        // n = 0 proves nothing, the search starts at 1
+        let (bounds, probes) = search(0, 0, None, 1);
+        assert_eq!(bounds.proven, None);
+        assert_eq!(bounds.failed, Some(1));
+        assert_eq!(probes, vec![1]);
+    }
+}
@@ -0,0 +1,247 @@
+use std::collections::HashMap;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::matrix::family;
+use crate::{
+    BenchmarkConfigAndResult, BenchmarkId, BenchmarkResultWithMemory, BenchmarkStatus, Session,
+    Trial,
+};
+
+/// Repeat every benchmark of a sweep several times in a random order,
+/// so that thermal drift and cache state do not systematically favour
+/// the benchmarks that happen to run first
+#[derive(Debug, Clone, Copy)]
+pub struct SessionOptions {
+    /// How often every benchmark is run
+    pub trials: u32,
+    /// Seed of the run order, recorded in every result
+    pub seed: u64,
+}
+
+impl SessionOptions {
+    /// The order to run `runs` in, as (index into `runs`, trial) pairs.
+    ///
+    /// Every trial is a round running all benchmarks in a fresh random order,
+    /// the same benchmark never runs twice in a row. Runs that only differ
+    /// in n keep ascending n within a round, so a smaller n exceeding the
+    /// limits still skips the larger ones.
+    pub fn schedule(&self, runs: &[BenchmarkId]) -> Vec<(usize, u32)> {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut families: HashMap<BenchmarkId, Vec<usize>> = HashMap::new();
+        for (index, id) in runs.iter().enumerate() {
+            families.entry(family(id)).or_default().push(index);
+        }
+        for members in families.values_mut() {
+            members.sort_by_key(|&index| runs[index].n);
+        }
+
+        let mut schedule: Vec<(usize, u32)> = Vec::with_capacity(runs.len() * self.trials as usize);
+        for trial in 0..self.trials {
+            let mut slots: Vec<usize> = (0..runs.len()).collect();
+            slots.shuffle(&mut rng);
+            let mut round = ascending_within_families(&slots, runs, &families);
+            // don't repeat the last benchmark of the previous round, that can
+            // only happen for a family with a single n, which then moves last
+            if runs.len() > 1 && schedule.last().map(|&(index, _)| index) == Some(round[0]) {
+                slots.swap(0, runs.len() - 1);
+                round = ascending_within_families(&slots, runs, &families);
+            }
+            schedule.extend(round.into_iter().map(|index| (index, trial)));
+        }
+        schedule
+    }
+
+    /// Merge the results of all trials of a benchmark so far into one result.
+    ///
+    /// The samples of all successful trials are combined, failures are only
+    /// reported if no trial succeeded. Every trial is kept in the session metadata.
+    pub fn merge(&self, trials: &[(usize, BenchmarkConfigAndResult)]) -> BenchmarkConfigAndResult {
+        let (_, first) = trials.first().expect("no trials to merge");
+
+        let successes: Vec<&BenchmarkResultWithMemory> = trials
+            .iter()
+            .filter_map(|(_, trial)| match &trial.result {
+                BenchmarkStatus::Success(result) => Some(result),
+                _ => None,
+            })
+            .collect();
+        let result = match successes.split_first() {
+            Some((&merged, rest)) => {
+                let mut merged = merged.clone();
+                for other in rest {
+                    merged.add_samples(other);
+                }
+                BenchmarkStatus::Success(merged)
+            }
+            None => first.result.clone(),
+        };
+
+        BenchmarkConfigAndResult {
+            result,
+            session: Some(Session {
+                seed: self.seed,
+                trials: trials
+                    .iter()
+                    .map(|(position, trial)| Trial {
+                        position: *position,
+                        started_at_unix: trial.provenance.as_ref().map(|p| p.started_at_unix),
+                        result: trial.result.clone(),
+                        hygiene: trial.hygiene.clone(),
+                    })
+                    .collect(),
+            }),
+            ..first.clone()
+        }
+    }
+}
+
+/// Give the slots taken by the runs of a family
+/// to that family's runs in ascending n
+fn ascending_within_families(
+    slots: &[usize],
+    runs: &[BenchmarkId],
+    families: &HashMap<BenchmarkId, Vec<usize>>,
+) -> Vec<usize> {
+    let mut taken: HashMap<BenchmarkId, usize> = HashMap::new();
+    slots
+        .iter()
+        .map(|&slot| {
+            let key = family(&runs[slot]);
+            let next = taken.entry(key.clone()).or_default();
+            let index = families[&key][*next];
+            *next += 1;
+            index
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::memory::MemoryResult;
+    use crate::{BenchmarkConfig, BenchmarkName, BenchmarkResult, Mode, VM};
+
+    fn id(program: &str, n: u32) -> BenchmarkId {
+        BenchmarkId {
+            name: BenchmarkName {
+                vm: VM::SP1,
+                program: program.to_string(),
+            },
+            n,
+            backend_options: Default::default(),
+            mode: Mode::Prove,
+        }
+    }
+
+    #[test]
+    fn test_session_schedule() {
+        let session = SessionOptions {
+            trials: 3,
+            seed: 42,
+        };
+        let runs = vec![id("fib", 1), id("sha2", 1), id("sha3", 1), id("ecdsa", 1)];
+        let schedule = session.schedule(&runs);
+
+        assert_eq!(schedule.len(), 12);
+        for index in 0..4 {
+            let trials: Vec<u32> = schedule
+                .iter()
+                .filter(|(i, _)| *i == index)
+                .map(|(_, trial)| *trial)
+                .collect();
+            assert_eq!(trials, vec![0, 1, 2]);
+        }
+        assert!(schedule.windows(2).all(|pair| pair[0].0 != pair[1].0));
+
+        // the order is reproducible from the seed
+        assert_eq!(schedule, session.schedule(&runs));
+        assert_ne!(
+            schedule,
+            SessionOptions { trials: 3, seed: 7 }.schedule(&runs)
+        );
+    }
+
+    #[test]
+    fn test_session_schedule_keeps_n_ascending() {
+        let runs = vec![
+            id("fib", 4096),
+            id("sha2", 1024),
+            id("fib", 1024),
+            id("sha2", 2048),
+            id("fib", 2048),
+            id("blake", 1024),
+        ];
+        for seed in 0..20 {
+            let schedule = SessionOptions { trials: 4, seed }.schedule(&runs);
+            assert_eq!(schedule.len(), 24);
+            assert!(schedule.windows(2).all(|pair| pair[0].0 != pair[1].0));
+            for trial in 0..4 {
+                for program in ["fib", "sha2"] {
+                    let n: Vec<u32> = schedule
+                        .iter()
+                        .filter(|&&(index, t)| t == trial && runs[index].name.program == program)
+                        .map(|&(index, _)| runs[index].n)
+                        .collect();
+                    assert!(n.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", n);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_session_merge() {
+        let trial = |prover_ms, peak_memory| BenchmarkConfigAndResult {
+            vm: VM::SP1,
+            config: BenchmarkConfig {
+                n: 4096,
+                program: "fib".to_string(),
+                verifier_iterations: 1,
+                ..Default::default()
+            },
+            result: BenchmarkStatus::Success(BenchmarkResultWithMemory {
+                result: BenchmarkResult {
+                    prover_durations: vec![Duration::from_millis(prover_ms)],
+                    verifier_durations: vec![Duration::from_millis(10)],
+                    ..Default::default()
+                },
+                peak_memory,
+            }),
+            hygiene: None,
+            provenance: None,
+            session: None,
+            sampling: None,
+        };
+        let failure = BenchmarkConfigAndResult {
+            result: BenchmarkStatus::Failure(MemoryResult::Signal(9)),
+            ..trial(0, 0)
+        };
+
+        let session = SessionOptions {
+            trials: 3,
+            seed: 42,
+        };
+        let merged = session.merge(&[(1, trial(100, 5)), (4, failure), (9, trial(120, 7))]);
+
+        let BenchmarkStatus::Success(result) = &merged.result else {
+            panic!("expected the successful trials to be merged");
+        };
+        assert_eq!(
+            result.result.prover_durations,
+            vec![Duration::from_millis(100), Duration::from_millis(120)]
+        );
+        assert_eq!(result.result.verifier_durations.len(), 2);
+        assert_eq!(result.peak_memory, 7);
+
+        let trials = &merged.session.as_ref().unwrap().trials;
+        assert_eq!(
+            trials.iter().map(|t| t.position).collect::<Vec<_>>(),
+            vec![1, 4, 9]
+        );
+        assert!(matches!(trials[1].result, BenchmarkStatus::Failure(_)));
+    }
+}
@@ -0,0 +1,184 @@
+/// Median of the samples, `None` if there are none
+pub fn median(samples: &[f64]) -> Option<f64> {
+    if samples.is_empty() {
+        return None;
+    }
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        Some((sorted[mid - 1] + sorted[mid]) / 2.0)
+    } else {
+        Some(sorted[mid])
+    }
+}
+
+/// Arithmetic mean of the samples, `None` if there are none
+pub fn mean(samples: &[f64]) -> Option<f64> {
+    if samples.is_empty() {
+        return None;
+    }
+    Some(samples.iter().sum::<f64>() / samples.len() as f64)
+}
+
+// sample standard deviation, `None` with less than two samples
+fn standard_deviation(samples: &[f64]) -> Option<f64> {
+    if samples.len() < 2 {
+        return None;
+    }
+    let mean = mean(samples)?;
+    let variance =
+        samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (samples.len() - 1) as f64;
+    Some(variance.sqrt())
+}
+
+/// Standard deviation relative to the mean (0.05 = 5%),
+/// `None` with less than two samples or a mean of zero
+pub fn coefficient_of_variation(samples: &[f64]) -> Option<f64> {
+    let mean = mean(samples).filter(|mean| *mean != 0.0)?;
+    Some(standard_deviation(samples)? / mean.abs())
+}
+
+/// Half-width of the 95% confidence interval of the mean, relative to the mean
+/// (0.05 = the mean is known to within ±5%), `None` as for the coefficient of variation
+pub fn confidence_interval(samples: &[f64]) -> Option<f64> {
+    let cv = coefficient_of_variation(samples)?;
+    let n = samples.len();
+    Some(student_t(n - 1) * cv / (n as f64).sqrt())
+}
+
+// two-sided 95% quantile of the Student t distribution
+fn student_t(degrees_of_freedom: usize) -> f64 {
+    const QUANTILES: [f64; 30] = [
+        12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160,
+        2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056,
+        2.052, 2.048, 2.045, 2.042,
+    ];
+    QUANTILES
+        .get(degrees_of_freedom.saturating_sub(1))
+        .copied()
+        .unwrap_or(1.96)
+}
+
+/// Two-sided p-value of the Mann-Whitney U test, i.e. how likely samples as
+/// different as `a` and `b` are if both come from the same distribution.
+///
+/// Uses the normal approximation with tie and continuity correction,
+/// `None` if either side has less than two samples.
+pub fn mann_whitney_u(a: &[f64], b: &[f64]) -> Option<f64> {
+    if a.len() < 2 || b.len() < 2 {
+        return None;
+    }
+    let (n1, n2) = (a.len() as f64, b.len() as f64);
+    let n = n1 + n2;
+
+    // rank all samples, ties get the average of their ranks
+    let mut all: Vec<(f64, bool)> = a
+        .iter()
+        .map(|&x| (x, true))
+        .chain(b.iter().map(|&x| (x, false)))
+        .collect();
+    all.sort_by(|x, y| x.0.total_cmp(&y.0));
+    let mut rank_sum_a = 0.0;
+    let mut tie_correction = 0.0;
+    let mut i = 0;
+    while i < all.len() {
+        let mut j = i;
+        while j < all.len() && all[j].0 == all[i].0 {
+            j += 1;
+        }
+        let rank = (i + j + 1) as f64 / 2.0;
+        rank_sum_a += rank * all[i..j].iter().filter(|(_, in_a)| *in_a).count() as f64;
+        let ties = (j - i) as f64;
+        tie_correction += ties.powi(3) - ties;
+        i = j;
+    }
+
+    let u = rank_sum_a - n1 * (n1 + 1.0) / 2.0;
+    let mean = n1 * n2 / 2.0;
+    let variance = n1 * n2 / 12.0 * ((n + 1.0) - tie_correction / (n * (n - 1.0)));
+    if variance <= 0.0 {
+        // all samples are equal
+        return Some(1.0);
+    }
+    let z = ((u - mean).abs() - 0.5).max(0.0) / variance.sqrt();
+    Some(erfc(z / std::f64::consts::SQRT_2).min(1.0))
+}
+
+// complementary error function, accurate to about 1e-7
+// (Numerical Recipes, Chebyshev approximation)
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let r = t
+        * (-z * z - 1.26551223
+            + t * (1.00002368
+                + t * (0.37409196
+                    + t * (0.09678418
+                        + t * (-0.18628806
+                            + t * (0.27886807
+                                + t * (-1.13520398
+                                    + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277)))))))))
+            .exp();
+    if x >= 0.0 {
+        r
+    } else {
+        2.0 - r
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median(&[]), None);
+        assert_eq!(median(&[3.0, 1.0, 2.0]), Some(2.0));
+        assert_eq!(median(&[4.0, 1.0, 3.0, 2.0]), Some(2.5));
+    }
+
+    #[test]
+    fn test_variation() {
+        assert_eq!(coefficient_of_variation(&[1.0]), None);
+        assert_eq!(coefficient_of_variation(&[0.0, 0.0]), None);
+        assert_eq!(coefficient_of_variation(&[2.0, 2.0, 2.0]), Some(0.0));
+
+        let samples = [9.0, 10.0, 11.0];
+        assert_eq!(mean(&samples), Some(10.0));
+        assert!((coefficient_of_variation(&samples).unwrap() - 0.1).abs() < 1e-9);
+        // t(2) = 4.303, so 4.303 * 0.1 / sqrt(3)
+        let ci = confidence_interval(&samples).unwrap();
+        assert!((ci - 0.248_434).abs() < 1e-6, "{}", ci);
+
+        // more samples narrow the interval
+        let more: Vec<f64> = samples.iter().cycle().take(30).copied().collect();
+        assert!(confidence_interval(&more).unwrap() < ci / 5.0);
+    }
+
+    #[test]
+    fn test_mann_whitney_u() {
+        assert_eq!(mann_whitney_u(&[1.0], &[2.0, 3.0]), None);
+        assert_eq!(mann_whitney_u(&[5.0, 5.0], &[5.0, 5.0]), Some(1.0));
+
+        // clearly shifted samples
+        let before = [10.0, 10.2, 9.9, 10.1, 10.0, 9.8, 10.3, 10.1];
+        let after = [11.0, 11.2, 10.9, 11.1, 11.0, 10.8, 11.3, 11.1];
+        let p = mann_whitney_u(&before, &after).unwrap();
+        assert!(p < 0.01, "p = {}", p);
+        assert_eq!(Some(p), mann_whitney_u(&after, &before));
+
+        // interleaved samples
+        let before = [10.0, 10.4, 9.8, 10.2, 10.1];
+        let after = [10.1, 9.9, 10.3, 10.0, 10.2];
+        let p = mann_whitney_u(&before, &after).unwrap();
+        assert!(p > 0.5, "p = {}", p);
+    }
+
+    #[test]
+    fn test_erfc() {
+        assert!((erfc(0.0) - 1.0).abs() < 1e-6);
+        assert!((erfc(1.0) - 0.157_299_207).abs() < 1e-6);
+        assert!((erfc(-1.0) - 1.842_700_793).abs() < 1e-6);
+    }
+}
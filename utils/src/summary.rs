@@ -0,0 +1,385 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use crate::compare::Metric;
+use crate::metrics::{Derived, DerivedMetric};
+use crate::pricing::{Instance, Pricing};
+use crate::runner::format_bytes;
+use crate::{stats, BenchmarkConfigAndResult, BenchmarkId, BenchmarkStatus, Mode, VM};
+
+// n -> column -> result
+type Rows<'a> = BTreeMap<u32, BTreeMap<String, &'a BenchmarkConfigAndResult>>;
+
+// (workload, n) -> native execution time in seconds
+type Baselines = BTreeMap<(String, u32), f64>;
+
+/// A table of the summary, a measured or derived metric or the cost on an instance
+#[derive(Debug, Clone, Copy)]
+enum Table<'a> {
+    Measured(Metric),
+    Derived(DerivedMetric),
+    CostPerProof(&'a Instance),
+    CostPerMbHashed(&'a Instance),
+    /// Prover time relative to running the workload natively
+    Overhead(&'a Baselines),
+}
+
+impl<'a> Table<'a> {
+    fn all(
+        pricing: Option<&'a Pricing>,
+        baselines: &'a Baselines,
+    ) -> impl Iterator<Item = Table<'a>> {
+        let instances = pricing.map_or(&[][..], |pricing| &pricing.instances);
+        Metric::ALL
+            .into_iter()
+            .map(Table::Measured)
+            .chain(DerivedMetric::ALL.into_iter().map(Table::Derived))
+            .chain(std::iter::once(Table::Overhead(baselines)))
+            .chain(instances.iter().map(Table::CostPerProof))
+            .chain(instances.iter().map(Table::CostPerMbHashed))
+    }
+
+    fn title(&self) -> String {
+        let instance = |instance: &Instance| {
+            format!(
+                "{} ({} cores, {} GiB, ${}/h)",
+                instance.name, instance.cores, instance.memory_gib, instance.dollars_per_hour
+            )
+        };
+        match self {
+            Table::Measured(metric) => capitalize(&metric.to_string()),
+            Table::Derived(metric) => capitalize(&metric.to_string()),
+            Table::CostPerProof(i) => format!("Cost per proof on {}", instance(i)),
+            Table::CostPerMbHashed(i) => format!("Cost per MB hashed on {}", instance(i)),
+            Table::Overhead(_) => "Proving overhead over native execution".to_string(),
+        }
+    }
+
+    /// Formatted value of a successful run, `None` if not measured
+    fn value(&self, result: &BenchmarkConfigAndResult) -> Option<String> {
+        let BenchmarkStatus::Success(success) = &result.result else {
+            return None;
+        };
+        match self {
+            Table::Measured(metric) => {
+                let median = stats::median(&metric.samples(success))?;
+                let noisy = matches!(metric, Metric::ProverTime | Metric::VerifierTime)
+                    && result.sampling.is_some_and(|sampling| !sampling.converged);
+                if noisy {
+                    return Some(format!("{} (noisy)", metric.format(median)));
+                }
+                Some(metric.format(median))
+            }
+            Table::Derived(metric) => {
+                let value = metric.value(&Derived::new(result))?;
+                Some(metric.format(value))
+            }
+            Table::CostPerProof(instance) | Table::CostPerMbHashed(instance) => {
+                let cost = instance.cost(result)?;
+                let dollars = match self {
+                    Table::CostPerMbHashed(_) => cost.dollars_per_mb_hashed?,
+                    _ => cost.dollars_per_proof,
+                };
+                if !cost.fits {
+                    return Some(format!("⚠️ needs {}", format_bytes(success.peak_memory)));
+                }
+                Some(format!("${:.4}", dollars))
+            }
+            Table::Overhead(baselines) => {
+                let id = result.id();
+                if id.name.vm == VM::Native {
+                    return None;
+                }
+                let key = (id.name.workload().0.to_string(), id.n);
+                let native = baselines.get(&key).filter(|secs| **secs > 0.0)?;
+                let prover = stats::median(&Metric::ProverTime.samples(success))?;
+                Some(format_factor(prover / native))
+            }
+        }
+    }
+}
+
+/// A factor like `350×` or `1.2e5×`
+fn format_factor(factor: f64) -> String {
+    if factor < 1000.0 {
+        format!("{:.0}×", factor)
+    } else {
+        format!("{:.1e}×", factor)
+    }
+}
+
+/// Native execution time of every workload and n the native baseline ran
+fn baselines(results: &[BenchmarkConfigAndResult]) -> Baselines {
+    results
+        .iter()
+        .filter(|result| result.vm == VM::Native)
+        .filter_map(|result| {
+            let BenchmarkStatus::Success(success) = &result.result else {
+                return None;
+            };
+            let execution = success.result.execution.as_ref()?;
+            let id = result.id();
+            let workload = id.name.workload().0.to_string();
+            Some(((workload, id.n), execution.duration.as_secs_f64()))
+        })
+        .collect()
+}
+
+/// Column of a table, the VM with the variant and backend options of the program
+fn column(id: &BenchmarkId) -> String {
+    let mut column = id.name.vm.to_string();
+    if let Some(variant) = id.name.workload().1 {
+        write!(column, "-{}", variant).unwrap();
+    }
+    for (key, value) in &id.backend_options {
+        write!(column, " {}={}", key, value).unwrap();
+    }
+    column
+}
+
+fn cell(table: Table<'_>, result: &BenchmarkConfigAndResult) -> Option<String> {
+    match &result.result {
+        BenchmarkStatus::Success(_) => table.value(result),
+        BenchmarkStatus::Failure(failure) => Some(format!("❌ {}", failure)),
+        BenchmarkStatus::Skipped(skipped) => {
+            Some(format!("⏭️ exceeds limits (n = {})", skipped.failed_run.n))
+        }
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// Render one section per workload with a table per metric,
+/// VMs as columns and n as rows. With `pricing`, the cost of
+/// every run on each of the instances is added. If the native baseline
+/// ran, the proving overhead over native execution is added as well.
+pub fn render(results: &[BenchmarkConfigAndResult], pricing: Option<&Pricing>) -> String {
+    let baselines = baselines(results);
+    let mut sections: BTreeMap<(String, Mode), Rows> = BTreeMap::new();
+    for result in results {
+        let id = result.id();
+        let workload = id.name.workload().0.to_string();
+        sections
+            .entry((workload, id.mode))
+            .or_default()
+            .entry(id.n)
+            .or_default()
+            .insert(column(&id), result);
+    }
+
+    let mut markdown = String::new();
+    for ((workload, mode), rows) in &sections {
+        match mode {
+            Mode::Prove => writeln!(markdown, "## {}\n", workload).unwrap(),
+            mode => writeln!(markdown, "## {} ({})\n", workload, mode).unwrap(),
+        }
+        let columns: BTreeSet<&String> = rows.values().flat_map(|row| row.keys()).collect();
+
+        for table in Table::all(pricing, &baselines) {
+            // skip metrics no run of the workload measured
+            let measured = rows
+                .values()
+                .flat_map(|row| row.values())
+                .any(|result| table.value(result).is_some());
+            if !measured {
+                continue;
+            }
+            let cells: BTreeMap<(u32, &String), String> = rows
+                .iter()
+                .flat_map(|(n, row)| {
+                    row.iter().filter_map(move |(column, result)| {
+                        Some(((*n, column), cell(table, result)?))
+                    })
+                })
+                .collect();
+
+            writeln!(markdown, "### {}\n", table.title()).unwrap();
+            let header: Vec<&str> = columns.iter().map(|column| column.as_str()).collect();
+            writeln!(markdown, "| n | {} |", header.join(" | ")).unwrap();
+            writeln!(markdown, "|---:|{}", "---:|".repeat(columns.len())).unwrap();
+            for n in rows.keys() {
+                let row: Vec<&str> = columns
+                    .iter()
+                    .map(|column| cells.get(&(*n, *column)).map_or("-", String::as_str))
+                    .collect();
+                writeln!(markdown, "| {} | {} |", n, row.join(" | ")).unwrap();
+            }
+            writeln!(markdown).unwrap();
+        }
+    }
+    markdown
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::memory::MemoryResult;
+    use crate::{
+        BenchmarkConfig, BenchmarkResult, BenchmarkResultWithMemory, ExecutionReport, Sampling,
+    };
+
+    fn result(vm: VM, program: &str, n: u32, result: BenchmarkStatus) -> BenchmarkConfigAndResult {
+        BenchmarkConfigAndResult {
+            vm,
+            config: BenchmarkConfig {
+                n,
+                program: program.to_string(),
+                verifier_iterations: 1,
+                ..Default::default()
+            },
+            result,
+            hygiene: None,
+            provenance: None,
+            session: None,
+            sampling: None,
+        }
+    }
+
+    fn success(prover_secs: u64) -> BenchmarkStatus {
+        BenchmarkStatus::Success(BenchmarkResultWithMemory {
+            result: BenchmarkResult {
+                prover_durations: vec![Duration::from_secs(prover_secs)],
+                ..Default::default()
+            },
+            peak_memory: 2_000_000_000,
+        })
+    }
+
+    #[test]
+    fn test_summary_tables() {
+        let results = vec![
+            result(VM::SP1, "sha2", 1024, success(10)),
+            result(VM::SP1, "sha2-precompile", 1024, success(2)),
+            result(VM::SP1, "sha2-precompile", 2048, success(4)),
+            result(
+                VM::Risc0,
+                "sha2",
+                2048,
+                BenchmarkStatus::Failure(MemoryResult::Signal(9)),
+            ),
+        ];
+
+        assert_eq!(
+            render(&results, None),
+            "## sha2\n\
+             \n\
+             ### Prover time\n\
+             \n\
+             | n | risc0 | sp1 | sp1-precompile |\n\
+             |---:|---:|---:|---:|\n\
+             | 1024 | - | 10.00 s | 2.00 s |\n\
+             | 2048 | ❌ out of memory | - | 4.00 s |\n\
+             \n\
+             ### Peak memory\n\
+             \n\
+             | n | risc0 | sp1 | sp1-precompile |\n\
+             |---:|---:|---:|---:|\n\
+             | 1024 | - | 2.00 GB | 2.00 GB |\n\
+             | 2048 | ❌ out of memory | - | 2.00 GB |\n\
+             \n\
+             ### Bytes hashed per second\n\
+             \n\
+             | n | risc0 | sp1 | sp1-precompile |\n\
+             |---:|---:|---:|---:|\n\
+             | 1024 | - | 102 B/s | 512 B/s |\n\
+             | 2048 | ❌ out of memory | - | 512 B/s |\n\
+             \n"
+        );
+    }
+
+    #[test]
+    fn test_summary_noisy() {
+        let mut noisy = result(VM::SP1, "fib", 1024, success(10));
+        noisy.sampling = Some(Sampling {
+            runs: 5,
+            converged: false,
+        });
+        let markdown = render(&[noisy], None);
+        assert!(
+            markdown.contains("| 1024 | 10.00 s (noisy) |"),
+            "{}",
+            markdown
+        );
+        assert!(markdown.contains("| 1024 | 2.00 GB |"), "{}", markdown);
+    }
+
+    #[test]
+    fn test_summary_cost() {
+        let pricing = Pricing::parse(
+            r#"
+            [[instance]]
+            name = "small"
+            dollars_per_hour = 3.6
+            cores = 16
+            memory_gib = 1
+            "#,
+        )
+        .unwrap();
+        let results = vec![
+            result(VM::SP1, "sha2", 1_000_000, success(10)),
+            result(VM::SP1, "sha2-precompile", 1_000_000, {
+                let BenchmarkStatus::Success(mut success) = success(1) else {
+                    unreachable!()
+                };
+                success.peak_memory = 500_000_000;
+                BenchmarkStatus::Success(success)
+            }),
+        ];
+
+        let markdown = render(&results, Some(&pricing));
+        assert!(markdown.contains(
+            "### Cost per proof on small (16 cores, 1 GiB, $3.6/h)\n\
+             \n\
+             | n | sp1 | sp1-precompile |\n\
+             |---:|---:|---:|\n\
+             | 1000000 | ⚠️ needs 2.00 GB | $0.0010 |\n"
+        ));
+        assert!(markdown.contains("### Cost per MB hashed on small"));
+    }
+
+    #[test]
+    fn test_summary_overhead() {
+        let native = BenchmarkStatus::Success(BenchmarkResultWithMemory {
+            result: BenchmarkResult {
+                execution: Some(ExecutionReport {
+                    duration: Duration::from_micros(50),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            peak_memory: 2_000_000,
+        });
+        let results = vec![
+            result(VM::Native, "sha2", 1024, native),
+            result(VM::SP1, "sha2", 1024, success(10)),
+            result(VM::SP1, "sha2-precompile", 1024, success(2)),
+            result(VM::SP1, "sha2-precompile", 2048, success(4)),
+        ];
+
+        let markdown = render(&results, None);
+        assert!(
+            markdown.contains(
+                "### Proving overhead over native execution\n\
+                 \n\
+                 | n | native | sp1 | sp1-precompile |\n\
+                 |---:|---:|---:|---:|\n\
+                 | 1024 | - | 2.0e5× | 4.0e4× |\n\
+                 | 2048 | - | - | - |\n"
+            ),
+            "{}",
+            markdown
+        );
+        assert_eq!(format_factor(350.4), "350×");
+
+        // without a native baseline there is no overhead table
+        assert!(!render(&results[1..], None).contains("overhead"));
+    }
+}